@@ -119,7 +119,7 @@ unsafe fn patch() -> Result<()> {
     let pak = repak::PakBuilder::new().reader(&mut pak_reader)?;
 
     let meta_buf = pak.get("meta", &mut pak_reader)?;
-    let meta: Meta = postcard::from_bytes(&meta_buf)?;
+    let meta = Meta::from_bytes(&meta_buf)?;
 
     let image = patternsleuth::process::internal::read_image()?;
     let resolution = image.resolve(hook_resolvers::HookResolution::resolver())?;
@@ -47,6 +47,8 @@ pub struct ModInfo {
     pub suggested_dependencies: Vec<ModSpecification>, // ModResponse
     pub modio_tags: Option<ModioTags>,                 // only available for mods from mod.io
     pub modio_id: Option<u32>,                         // only available for mods from mod.io
+    pub author: Option<String>,                        // if known to the provider
+    pub version: Option<String>,                       // version name of the resolution if known
 }
 
 /// Returned from ModProvider
@@ -68,10 +70,6 @@ impl ModSpecification {
     pub fn new(url: String) -> Self {
         Self { url }
     }
-    pub fn satisfies_dependency(&self, other: &ModSpecification) -> bool {
-        // TODO this hack works surprisingly well but is still a complete hack and should be replaced
-        self.url.starts_with(&other.url) || other.url.starts_with(&self.url)
-    }
 }
 
 /// Points to a specific version of a specific mod
@@ -123,12 +121,19 @@ impl From<&str> for ModIdentifier {
     }
 }
 
+/// Format of the serialized [`Meta`]. Bundles of format 0 contain placeholders instead of mod
+/// versions and authors.
+pub const META_FORMAT_VERSION: u32 = 1;
+
 /// Stripped down mod info stored in the mod pak to be used in game
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Meta {
     pub version: SemverVersion,
     pub mods: Vec<MetaMod>,
     pub config: MetaConfig,
+    /// Written after the struct by [`Meta::to_bytes`] rather than as part of it.
+    #[serde(skip)]
+    pub format_version: u32,
 }
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MetaConfig {}
@@ -153,27 +158,156 @@ pub struct MetaMod {
     pub required: bool,
 }
 impl Meta {
+    /// Serialize as postcard followed by the format version. Hooks that predate the format
+    /// version stop reading after the struct and ignore it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = postcard::to_allocvec(self).unwrap();
+        buf.extend(postcard::to_allocvec(&self.format_version).unwrap());
+        buf
+    }
+
+    /// Deserialize meta written by [`Meta::to_bytes`] of any format version.
+    pub fn from_bytes(buf: &[u8]) -> Result<Self, postcard::Error> {
+        let (mut meta, rest) = postcard::take_from_bytes::<Self>(buf)?;
+        meta.format_version = if rest.is_empty() {
+            0
+        } else {
+            postcard::from_bytes(rest)?
+        };
+        Ok(meta)
+    }
+
+    /// `mint;<version>` followed by an approval and name field per mod. From format version 1
+    /// on, a single `v:` field listing the mod versions in the same order separated by `,`
+    /// follows the mods. Being one field, it never forms another approval and name pair for
+    /// readers that only know those, and the prefix keeps it from ever matching an approval.
     pub fn to_server_list_string(&self) -> String {
         use itertools::Itertools;
 
+        let mods = self
+            .mods
+            .iter()
+            .sorted_by_key(|m| (std::cmp::Reverse(m.approval), &m.name))
+            .collect::<Vec<_>>();
+        let versions = (self.format_version >= 1).then(|| {
+            format!(
+                "v:{}",
+                mods.iter()
+                    .map(|m| m.version.replace([';', ','], ""))
+                    .join(",")
+            )
+        });
+
         ["mint".into(), self.version.to_string()]
             .into_iter()
-            .chain(
-                self.mods
-                    .iter()
-                    .sorted_by_key(|m| (std::cmp::Reverse(m.approval), &m.name))
-                    .flat_map(|m| {
-                        [
-                            match m.approval {
-                                ApprovalStatus::Verified => 'V',
-                                ApprovalStatus::Approved => 'A',
-                                ApprovalStatus::Sandbox => 'S',
-                            }
-                            .into(),
-                            m.name.replace(';', ""),
-                        ]
-                    }),
-            )
+            .chain(mods.iter().flat_map(|m| {
+                [
+                    match m.approval {
+                        ApprovalStatus::Verified => 'V',
+                        ApprovalStatus::Approved => 'A',
+                        ApprovalStatus::Sandbox => 'S',
+                    }
+                    .into(),
+                    m.name.replace(';', ""),
+                ]
+            }))
+            .chain(versions)
             .join(";")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn meta(format_version: u32) -> Meta {
+        Meta {
+            version: SemverVersion {
+                major: 0,
+                minor: 3,
+                patch: 0,
+            },
+            mods: vec![MetaMod {
+                name: "Mod".into(),
+                version: "1.2".into(),
+                url: "https://mod.io/g/drg/m/mod#1/2".into(),
+                author: "someone".into(),
+                approval: ApprovalStatus::Approved,
+                required: true,
+            }],
+            config: MetaConfig {},
+            format_version,
+        }
+    }
+
+    #[test]
+    fn test_format_version() {
+        let buf = meta(META_FORMAT_VERSION).to_bytes();
+        let read = Meta::from_bytes(&buf).unwrap();
+        assert_eq!(read.format_version, META_FORMAT_VERSION);
+        assert_eq!(read.to_server_list_string(), "mint;0.3.0;A;Mod;v:1.2");
+
+        // format 0 as written before the format version existed
+        let legacy = postcard::to_allocvec(&meta(0)).unwrap();
+        let read = Meta::from_bytes(&legacy).unwrap();
+        assert_eq!(read.format_version, 0);
+        assert_eq!(read.to_server_list_string(), "mint;0.3.0;A;Mod");
+
+        // readers predating the format version ignore it
+        let read = postcard::from_bytes::<Meta>(&buf).unwrap();
+        assert_eq!(read.mods[0].author, "someone");
+    }
+
+    #[test]
+    fn test_server_list_reader() {
+        /// Mods as read by the server list tooltip (TOOLTIP_ServerEntry_Mods): the fields after
+        /// the header are split into `(fields - 2) / 2` approval and name pairs, approvals
+        /// compared against `V`, `A` and `S`.
+        fn read_mods(entry: &str) -> Vec<(Option<ApprovalStatus>, &str)> {
+            let fields = entry.split(';').collect::<Vec<_>>();
+            (0..(fields.len() - 2) / 2)
+                .map(|i| {
+                    let approval = match fields[2 + i * 2] {
+                        "V" => Some(ApprovalStatus::Verified),
+                        "A" => Some(ApprovalStatus::Approved),
+                        "S" => Some(ApprovalStatus::Sandbox),
+                        _ => None,
+                    };
+                    (approval, fields[3 + i * 2])
+                })
+                .collect()
+        }
+
+        let mut meta = meta(META_FORMAT_VERSION);
+        meta.mods.push(MetaMod {
+            name: "Other;Mod".into(),
+            version: "S".into(),
+            url: "https://example.org/other.pak".into(),
+            author: "".into(),
+            approval: ApprovalStatus::Sandbox,
+            required: false,
+        });
+        meta.mods.push(MetaMod {
+            name: "Third".into(),
+            version: "A,1".into(),
+            url: "https://example.org/third.pak".into(),
+            author: "".into(),
+            approval: ApprovalStatus::Sandbox,
+            required: false,
+        });
+        let entry = meta.to_server_list_string();
+        assert_eq!(entry, "mint;0.3.0;A;Mod;S;OtherMod;S;Third;v:1.2,S,A1");
+        let mods = [
+            (Some(ApprovalStatus::Approved), "Mod"),
+            (Some(ApprovalStatus::Sandbox), "OtherMod"),
+            (Some(ApprovalStatus::Sandbox), "Third"),
+        ];
+        assert_eq!(read_mods(&entry), mods);
+
+        meta.mods.pop();
+        assert_eq!(read_mods(&meta.to_server_list_string()), mods[..2]);
+
+        meta.format_version = 0;
+        assert_eq!(read_mods(&meta.to_server_list_string()), mods[..2]);
+    }
+}
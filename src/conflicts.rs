@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use tracing::*;
+
+use crate::providers::ModSpecification;
+
+/// Paths that are expected to be shipped by several mods and are never reported as conflicts.
+pub(crate) const CONFLICT_WHITELIST: [&str; 1] = ["fsd/content/_interop"];
+
+/// Decides which mod's copy of a file ends up in the bundle when several mods contain it.
+///
+/// Mods are expected in ascending order of precedence (see
+/// [`crate::state::ModData_v0_1_0::enabled_mods_by_priority`]), so by default
+/// the last mod containing a path wins. An override pins a path to a specific mod instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConflictRules {
+    /// Lowercase path relative to the pak root, e.g. `fsd/content/ui/foo` or
+    /// `fsd/content/ui/foo.uasset`, mapped to the mod that should win it. Either covers all
+    /// files of the asset (see [`asset_path`]) and a directory covers everything below it. The
+    /// longest matching path takes precedence.
+    pub overrides: BTreeMap<String, ModSpecification>,
+}
+
+impl ConflictRules {
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// Map the mods of all overrides through `f`, e.g. to match them against canonical
+    /// specifications.
+    pub fn map_specs<F: FnMut(&ModSpecification) -> ModSpecification>(&self, mut f: F) -> Self {
+        Self {
+            overrides: self
+                .overrides
+                .iter()
+                .map(|(path, spec)| (path.clone(), f(spec)))
+                .collect(),
+        }
+    }
+
+    fn preferred(&self, asset: &str) -> Option<&ModSpecification> {
+        self.overrides
+            .iter()
+            .map(|(key, spec)| {
+                let key = key.to_ascii_lowercase();
+                let key = match key.strip_suffix('/') {
+                    Some(dir) => dir.trim_end_matches('/').to_string(),
+                    None => asset_path(&key).to_string(),
+                };
+                (key, spec)
+            })
+            .filter(|(key, _)| {
+                asset == key
+                    || asset
+                        .strip_prefix(key.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, spec)| spec)
+    }
+
+    fn asset_winner(&self, asset: &str, mods: &[&ModSpecification]) -> usize {
+        assert!(!mods.is_empty());
+        self.preferred(asset)
+            .and_then(|preferred| mods.iter().rposition(|m| *m == preferred))
+            .unwrap_or(mods.len() - 1)
+    }
+
+    /// Index into `mods` of the mod whose copy of the asset of the lowercase `path` wins.
+    pub fn winner(&self, path: &str, mods: &[&ModSpecification]) -> usize {
+        self.asset_winner(asset_path(path), mods)
+    }
+
+    /// Index into `mods` of the winning mod for each lowercase path of `per_path_mods`, which maps
+    /// paths to the indices of the mods containing them. Mods are expected in ascending order of
+    /// precedence. All files of an asset are won by the same mod, even if it lacks some of them,
+    /// as files of different mods don't make up a working asset.
+    pub fn winners(
+        &self,
+        per_path_mods: &BTreeMap<String, Vec<usize>>,
+        mods: &[&ModSpecification],
+    ) -> HashMap<String, usize> {
+        let mut assets: BTreeMap<&str, BTreeSet<usize>> = BTreeMap::new();
+        for (path, indices) in per_path_mods {
+            assets.entry(asset_path(path)).or_default().extend(indices);
+        }
+        let asset_winners = assets
+            .into_iter()
+            .map(|(asset, indices)| {
+                let indices = indices.into_iter().collect::<Vec<_>>();
+                let specs = indices.iter().map(|i| mods[*i]).collect::<Vec<_>>();
+                (asset, indices[self.asset_winner(asset, &specs)])
+            })
+            .collect::<HashMap<_, _>>();
+        per_path_mods
+            .keys()
+            .map(|path| (path.clone(), asset_winners[asset_path(path)]))
+            .collect()
+    }
+}
+
+/// Path of the asset a file belongs to, the path without the extension of its file name, e.g.
+/// `fsd/content/ui/foo` for `fsd/content/ui/foo.uexp`.
+pub fn asset_path(path: &str) -> &str {
+    let file_name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[file_name_start..].rfind('.') {
+        Some(dot) if dot > 0 => &path[..file_name_start + dot],
+        _ => path,
+    }
+}
+
+/// A path contained in more than one mod.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conflict {
+    /// All mods containing the path in order of precedence.
+    pub mods: Vec<ModSpecification>,
+    /// The mod whose copy is used.
+    pub winner: ModSpecification,
+}
+
+/// Conflicts keyed by lowercase path.
+pub type ConflictReport = BTreeMap<String, Conflict>;
+
+/// Build a conflict report from the indices into `mods` of the mods containing each lowercase
+/// path, see [`ConflictRules::winners`].
+pub fn find_conflicts(
+    per_path_mods: &BTreeMap<String, Vec<usize>>,
+    mods: &[&ModSpecification],
+    rules: &ConflictRules,
+) -> ConflictReport {
+    let winners = rules.winners(per_path_mods, mods);
+    per_path_mods
+        .iter()
+        .filter(|(path, indices)| {
+            indices.len() > 1
+                && !CONFLICT_WHITELIST
+                    .iter()
+                    .any(|whitelisted| path.starts_with(whitelisted))
+        })
+        .map(|(path, indices)| {
+            let conflict = Conflict {
+                mods: indices.iter().map(|i| mods[*i].clone()).collect(),
+                winner: mods[winners[path]].clone(),
+            };
+            (path.clone(), conflict)
+        })
+        .collect()
+}
+
+pub fn log_conflicts(report: &ConflictReport) {
+    if report.is_empty() {
+        return;
+    }
+    info!("{} files are contained in more than one mod:", report.len());
+    for (path, conflict) in report {
+        info!(
+            "  {path}: using {} (also in {})",
+            conflict.winner.url,
+            conflict
+                .mods
+                .iter()
+                .filter(|m| **m != conflict.winner)
+                .map(|m| m.url.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spec(url: &str) -> ModSpecification {
+        ModSpecification::new(url.to_string())
+    }
+
+    #[test]
+    fn test_last_wins() {
+        let (a, b) = (spec("a"), spec("b"));
+        let rules = ConflictRules::default();
+        assert_eq!(rules.winner("fsd/content/a.uasset", &[&a, &b]), 1);
+    }
+
+    #[test]
+    fn test_override() {
+        let (a, b, c) = (spec("a"), spec("b"), spec("c"));
+        let rules = ConflictRules {
+            overrides: [
+                ("fsd/content/ui/".to_string(), a.clone()),
+                ("FSD/Content/UI/Foo".to_string(), b.clone()),
+            ]
+            .into(),
+        };
+        let mods = [&a, &b, &c];
+        assert_eq!(rules.winner("fsd/content/ui/bar.uasset", &mods), 0);
+        assert_eq!(rules.winner("fsd/content/ui/foo.uexp", &mods), 1);
+        assert_eq!(rules.winner("fsd/content/ui/foobar.uexp", &mods), 0);
+        assert_eq!(rules.winner("fsd/content/uix.uexp", &mods), 2);
+        // preferred mod does not contain the path
+        assert_eq!(rules.winner("fsd/content/ui/bar.uasset", &[&b, &c]), 1);
+
+        // an override for one file covers the other files of the asset
+        let rules = ConflictRules {
+            overrides: [("fsd/content/ui/foo.uasset".to_string(), a.clone())].into(),
+        };
+        assert_eq!(rules.winner("fsd/content/ui/foo.ubulk", &mods), 0);
+    }
+
+    #[test]
+    fn test_asset_path() {
+        assert_eq!(asset_path("fsd/content/ui/foo.uexp"), "fsd/content/ui/foo");
+        assert_eq!(asset_path("fsd/content/ui/foo"), "fsd/content/ui/foo");
+        assert_eq!(asset_path("fsd/content.dir/foo"), "fsd/content.dir/foo");
+        assert_eq!(asset_path("fsd/.hidden"), "fsd/.hidden");
+    }
+
+    #[test]
+    fn test_winners_per_asset() {
+        let (a, b) = (spec("a"), spec("b"));
+        let per_path_mods = BTreeMap::from([
+            ("fsd/content/foo.uasset".to_string(), vec![0, 1]),
+            ("fsd/content/foo.uexp".to_string(), vec![0]),
+            ("fsd/content/bar.uasset".to_string(), vec![0, 1]),
+            ("fsd/content/bar.uexp".to_string(), vec![0, 1]),
+        ]);
+        let rules = ConflictRules {
+            overrides: [("fsd/content/bar.uexp".to_string(), a.clone())].into(),
+        };
+        let winners = rules.winners(&per_path_mods, &[&a, &b]);
+        // b wins foo, so a's foo.uexp must not be combined with b's foo.uasset
+        assert_eq!(winners["fsd/content/foo.uexp"], 1);
+        assert_eq!(winners["fsd/content/bar.uasset"], 0);
+        assert_eq!(winners["fsd/content/bar.uexp"], 0);
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let (a, b) = (spec("a"), spec("b"));
+        let report = find_conflicts(
+            &[
+                ("fsd/content/a.uasset".to_string(), vec![0, 1]),
+                ("fsd/content/b.uasset".to_string(), vec![1]),
+                ("fsd/content/_interop/c.uasset".to_string(), vec![0, 1]),
+            ]
+            .into(),
+            &[&a, &b],
+            &ConflictRules::default(),
+        );
+        assert_eq!(report.len(), 1);
+        assert_eq!(report["fsd/content/a.uasset"].winner, b);
+    }
+}
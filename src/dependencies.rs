@@ -0,0 +1,397 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+use tracing::*;
+
+use crate::providers::{ModInfo, ModSpecification};
+
+/// What to do with dependencies of the requested mods which are not requested themselves.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingDependencyPolicy {
+    /// Integrate missing dependencies along with the mods that require them. Refuses to integrate
+    /// if one of them could not be resolved.
+    #[default]
+    Add,
+    /// Refuse to integrate.
+    Fail,
+    /// Integrate without them and only emit a warning.
+    Warn,
+}
+
+impl MissingDependencyPolicy {
+    pub const ALL: [Self; 3] = [Self::Add, Self::Fail, Self::Warn];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Add => "add automatically",
+            Self::Fail => "refuse to integrate",
+            Self::Warn => "only warn",
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum DependencyError {
+    #[snafu(display("missing dependencies:{}", format_missing(missing)))]
+    MissingDependencies {
+        /// Missing dependency and the mods that require it.
+        missing: BTreeMap<ModSpecification, Vec<ModSpecification>>,
+    },
+    #[snafu(display(
+        "dependency cycle: {}",
+        cycle.iter().map(|s| s.url.as_str()).collect::<Vec<_>>().join(" -> ")
+    ))]
+    DependencyCycle { cycle: Vec<ModSpecification> },
+}
+
+fn format_missing(missing: &BTreeMap<ModSpecification, Vec<ModSpecification>>) -> String {
+    let mut msg = String::new();
+    for (dependency, required_by) in missing {
+        msg.push_str(&format!(
+            "\n  {} (required by {})",
+            dependency.url,
+            required_by
+                .iter()
+                .map(|s| s.url.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    msg
+}
+
+/// Requested mods together with the dependencies they pulled in.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedDependencies {
+    /// Mods to integrate in ascending order of precedence: the added dependencies followed by the
+    /// requested mods in their original order. Dependencies come before all of the mods requiring
+    /// them so those take precedence in case of conflicts.
+    pub mods: Vec<(ModSpecification, ModInfo)>,
+    /// Dependencies that were not requested and had to be added, along with the mods that pulled
+    /// them in.
+    pub added: IndexMap<ModSpecification, Vec<ModSpecification>>,
+}
+
+impl ResolvedDependencies {
+    pub fn log_added(&self) {
+        if self.added.is_empty() {
+            return;
+        }
+        info!("adding missing dependencies:");
+        for (dependency, required_by) in &self.added {
+            info!(
+                "  {} (required by {})",
+                dependency.url,
+                required_by
+                    .iter()
+                    .map(|s| s.url.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+}
+
+/// Build the dependency graph of `requested` from the mods returned by
+/// [`crate::providers::ModStore::resolve_mods`] and compute the closure to integrate.
+///
+/// Mods are identified by their unpinned [`ModInfo::spec`] so a pinned version of a mod satisfies
+/// a dependency on the mod itself.
+pub fn resolve_dependencies(
+    requested: &[ModSpecification],
+    resolved: &HashMap<ModSpecification, ModInfo>,
+    policy: MissingDependencyPolicy,
+) -> Result<ResolvedDependencies, DependencyError> {
+    // `resolve_mods` skips dependencies on mods it has already resolved under a different spec,
+    // so look mods up by their canonical spec as well
+    let by_canonical = resolved
+        .values()
+        .map(|info| (&info.spec, info))
+        .collect::<HashMap<_, _>>();
+    let lookup = |spec: &ModSpecification| {
+        resolved
+            .get(spec)
+            .or_else(|| by_canonical.get(spec).copied())
+    };
+    let canonical = |spec: &ModSpecification| {
+        lookup(spec)
+            .map(|info| info.spec.clone())
+            .unwrap_or_else(|| spec.clone())
+    };
+
+    let graph = resolved
+        .values()
+        .map(|info| {
+            (
+                info.spec.clone(),
+                info.suggested_dependencies
+                    .iter()
+                    .map(canonical)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut mods = requested
+        .iter()
+        .filter_map(|spec| lookup(spec).map(|info| (spec.clone(), info.clone())))
+        .collect::<Vec<_>>();
+
+    if let Some(cycle) = find_cycle(&graph) {
+        ensure!(
+            policy == MissingDependencyPolicy::Warn,
+            DependencyCycleSnafu { cycle }
+        );
+        // mutually dependent mods used to integrate fine in the order they were requested
+        warn!(
+            "{}, integrating mods in the requested order",
+            DependencyCycleSnafu { cycle }.build()
+        );
+        return Ok(ResolvedDependencies {
+            mods,
+            added: Default::default(),
+        });
+    }
+
+    let requested_canonical = requested.iter().map(canonical).collect::<HashSet<_>>();
+
+    // reverse post-order of a depth first search is a topological order, so every dependency
+    // ends up after all of the mods requiring it here and before them once reversed again below
+    fn visit(
+        spec: &ModSpecification,
+        graph: &HashMap<ModSpecification, Vec<ModSpecification>>,
+        visited: &mut HashSet<ModSpecification>,
+        order: &mut Vec<ModSpecification>,
+    ) {
+        if !visited.insert(spec.clone()) {
+            return;
+        }
+        for dependency in graph.get(spec).into_iter().flatten() {
+            visit(dependency, graph, visited, order);
+        }
+        order.push(spec.clone());
+    }
+    let mut order = vec![];
+    let mut visited = HashSet::new();
+    for spec in requested.iter().rev() {
+        visit(&canonical(spec), &graph, &mut visited, &mut order);
+    }
+    order.reverse();
+
+    let mut missing: IndexMap<ModSpecification, Vec<ModSpecification>> = order
+        .iter()
+        .filter(|spec| !requested_canonical.contains(*spec))
+        .map(|spec| (spec.clone(), vec![]))
+        .collect();
+    for spec in &order {
+        for dependency in graph.get(spec).into_iter().flatten() {
+            if let Some(required_by) = missing.get_mut(dependency)
+                && !required_by.contains(spec)
+            {
+                required_by.push(spec.clone());
+            }
+        }
+    }
+
+    match policy {
+        MissingDependencyPolicy::Add => {
+            // dependencies that could not be resolved cannot be added either
+            let unresolved = missing
+                .iter()
+                .filter(|(dependency, _)| lookup(dependency).is_none())
+                .map(|(dependency, required_by)| (dependency.clone(), required_by.clone()))
+                .collect::<BTreeMap<_, _>>();
+            ensure!(
+                unresolved.is_empty(),
+                MissingDependenciesSnafu {
+                    missing: unresolved
+                }
+            );
+            mods.splice(
+                0..0,
+                missing
+                    .keys()
+                    .rev()
+                    .map(|dependency| (dependency.clone(), lookup(dependency).unwrap().clone())),
+            );
+            Ok(ResolvedDependencies {
+                mods,
+                added: missing,
+            })
+        }
+        MissingDependencyPolicy::Fail if !missing.is_empty() => MissingDependenciesSnafu {
+            missing: missing.into_iter().collect::<BTreeMap<_, _>>(),
+        }
+        .fail(),
+        MissingDependencyPolicy::Fail => Ok(ResolvedDependencies {
+            mods,
+            added: Default::default(),
+        }),
+        MissingDependencyPolicy::Warn => {
+            if !missing.is_empty() {
+                warn!(
+                    "the following dependencies are missing:{}",
+                    format_missing(&missing.into_iter().collect())
+                );
+            }
+            Ok(ResolvedDependencies {
+                mods,
+                added: Default::default(),
+            })
+        }
+    }
+}
+
+fn find_cycle(
+    graph: &HashMap<ModSpecification, Vec<ModSpecification>>,
+) -> Option<Vec<ModSpecification>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        InProgress,
+        Done,
+    }
+
+    fn visit<'a>(
+        spec: &'a ModSpecification,
+        graph: &'a HashMap<ModSpecification, Vec<ModSpecification>>,
+        marks: &mut HashMap<&'a ModSpecification, Mark>,
+        stack: &mut Vec<&'a ModSpecification>,
+    ) -> Option<Vec<ModSpecification>> {
+        match marks.get(spec) {
+            Some(Mark::Done) => return None,
+            Some(Mark::InProgress) => {
+                let start = stack.iter().position(|s| *s == spec).unwrap();
+                return Some(
+                    stack[start..]
+                        .iter()
+                        .chain([&spec])
+                        .map(|s| (*s).clone())
+                        .collect(),
+                );
+            }
+            None => {}
+        }
+        marks.insert(spec, Mark::InProgress);
+        stack.push(spec);
+        for dependency in graph.get(spec).into_iter().flatten() {
+            if let Some(cycle) = visit(dependency, graph, marks, stack) {
+                return Some(cycle);
+            }
+        }
+        stack.pop();
+        marks.insert(spec, Mark::Done);
+        None
+    }
+
+    let mut marks = HashMap::new();
+    // sorted for deterministic reports
+    let mut nodes = graph.keys().collect::<Vec<_>>();
+    nodes.sort();
+    nodes
+        .into_iter()
+        .find_map(|spec| visit(spec, graph, &mut marks, &mut vec![]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::providers::ModResolution;
+
+    fn spec(url: &str) -> ModSpecification {
+        ModSpecification::new(url.to_string())
+    }
+
+    fn info(url: &str, deps: &[&str]) -> ModInfo {
+        ModInfo {
+            provider: "test",
+            name: url.to_string(),
+            author: None,
+            version: None,
+            spec: spec(url),
+            versions: vec![],
+            resolution: ModResolution::resolvable(url.into()),
+            suggested_require: false,
+            suggested_dependencies: deps.iter().map(|d| spec(d)).collect(),
+            modio_tags: None,
+            modio_id: None,
+        }
+    }
+
+    fn resolved(infos: &[ModInfo]) -> HashMap<ModSpecification, ModInfo> {
+        infos.iter().map(|i| (i.spec.clone(), i.clone())).collect()
+    }
+
+    #[test]
+    fn test_add_missing() {
+        let mods = resolved(&[info("a", &["b"]), info("b", &["c"]), info("c", &[])]);
+        let res = resolve_dependencies(&[spec("a")], &mods, MissingDependencyPolicy::Add).unwrap();
+        assert_eq!(
+            res.mods.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
+            vec![spec("c"), spec("b"), spec("a")]
+        );
+        assert_eq!(res.added[&spec("b")], vec![spec("a")]);
+        assert_eq!(res.added[&spec("c")], vec![spec("b")]);
+    }
+
+    #[test]
+    fn test_add_unresolved() {
+        let mods = resolved(&[info("a", &["b"])]);
+        let Err(DependencyError::MissingDependencies { missing }) =
+            resolve_dependencies(&[spec("a")], &mods, MissingDependencyPolicy::Add)
+        else {
+            panic!("expected missing dependencies");
+        };
+        assert_eq!(missing, [(spec("b"), vec![spec("a")])].into());
+    }
+
+    #[test]
+    fn test_pinned_satisfies_dependency() {
+        let mut pinned = info("a", &[]);
+        pinned.resolution = ModResolution::resolvable("a#1".into());
+        let mut mods = resolved(&[info("b", &["a"])]);
+        mods.insert(spec("a#1"), pinned);
+        let res = resolve_dependencies(
+            &[spec("a#1"), spec("b")],
+            &mods,
+            MissingDependencyPolicy::Fail,
+        )
+        .unwrap();
+        assert!(res.added.is_empty());
+        assert_eq!(res.mods.len(), 2);
+    }
+
+    #[test]
+    fn test_fail_missing() {
+        let mods = resolved(&[info("a", &["b"]), info("b", &[])]);
+        assert!(matches!(
+            resolve_dependencies(&[spec("a")], &mods, MissingDependencyPolicy::Fail),
+            Err(DependencyError::MissingDependencies { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mods = resolved(&[info("a", &["b"]), info("b", &["a"])]);
+        let Err(DependencyError::DependencyCycle { cycle }) =
+            resolve_dependencies(&[spec("a")], &mods, MissingDependencyPolicy::Add)
+        else {
+            panic!("expected cycle");
+        };
+        assert_eq!(cycle, vec![spec("a"), spec("b"), spec("a")]);
+
+        let res = resolve_dependencies(
+            &[spec("b"), spec("a")],
+            &mods,
+            MissingDependencyPolicy::Warn,
+        )
+        .unwrap();
+        assert_eq!(
+            res.mods.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
+            vec![spec("b"), spec("a")]
+        );
+        assert!(res.added.is_empty());
+    }
+}
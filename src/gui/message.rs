@@ -15,6 +15,8 @@ use super::{
     request_counter::{RequestCounter, RequestID},
     App, SpecFetchProgress, WindowProviderParameters,
 };
+use crate::conflicts::ConflictRules;
+use crate::dependencies::{resolve_dependencies, MissingDependencyPolicy};
 use crate::gui::LastAction;
 use crate::integrate::*;
 use crate::mod_lints::{LintId, LintReport};
@@ -109,6 +111,7 @@ impl ResolveMods {
                         .specs
                         .into_iter()
                         .collect::<HashSet<ModSpecification>>();
+                    let store = app.state.store.clone();
                     for (resolved_spec, info) in resolved_mods {
                         let is_dep = self.is_dependency || !primary_mods.contains(&resolved_spec);
                         let add = if is_dep {
@@ -120,7 +123,7 @@ impl ResolveMods {
                             !app.state.mod_data.any_mod_mut(
                                 &active_profile,
                                 |mc, mod_group_enabled| {
-                                    if mc.spec.satisfies_dependency(&resolved_spec) {
+                                    if store.canonical_spec(&mc.spec) == info.spec {
                                         mc.enabled = true;
                                         if let Some(mod_group_enabled) = mod_group_enabled {
                                             *mod_group_enabled = true;
@@ -182,11 +185,15 @@ pub struct Integrate {
 }
 
 impl Integrate {
+    #[allow(clippy::too_many_arguments)]
     pub fn send(
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
+        policy: MissingDependencyPolicy,
+        rules: ConflictRules,
         fsd_pak: PathBuf,
+        target: IntegrationTarget,
         config: MetaConfig,
         tx: Sender<Message>,
         ctx: egui::Context,
@@ -195,9 +202,19 @@ impl Integrate {
         MessageHandle {
             rid,
             handle: tokio::task::spawn(async move {
-                let res =
-                    integrate_async(store, ctx.clone(), mods, fsd_pak, config, rid, tx.clone())
-                        .await;
+                let res = integrate_async(
+                    store,
+                    ctx.clone(),
+                    mods,
+                    policy,
+                    rules,
+                    fsd_pak,
+                    target,
+                    config,
+                    rid,
+                    tx.clone(),
+                )
+                .await;
                 tx.send(Message::Integrate(Integrate { rid, result: res }))
                     .await
                     .unwrap();
@@ -212,7 +229,13 @@ impl Integrate {
             match self.result {
                 Ok(()) => {
                     info!("integration complete");
-                    app.last_action = Some(LastAction::success("integration complete".to_string()));
+                    app.last_action = Some(LastAction::success(match app.watch_changes.take() {
+                        Some(changes) => format!("integrated changes: {changes}"),
+                        None => "integration complete".to_string(),
+                    }));
+                    if let Some(window) = &mut app.history_window {
+                        window.refresh(&app.state);
+                    }
                 }
                 Err(ref e)
                     if let IntegrationError::ProviderError { ref source } = e
@@ -229,6 +252,7 @@ impl Integrate {
                 }
             }
             app.integrate_rid = None;
+            app.watch_changes = None;
         }
     }
 }
@@ -352,11 +376,15 @@ impl CheckUpdates {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn integrate_async(
     store: Arc<ModStore>,
     ctx: egui::Context,
     mod_specs: Vec<ModSpecification>,
+    policy: MissingDependencyPolicy,
+    rules: ConflictRules,
     fsd_pak: PathBuf,
+    target: IntegrationTarget,
     config: MetaConfig,
     rid: RequestID,
     message_tx: Sender<Message>,
@@ -365,9 +393,13 @@ async fn integrate_async(
 
     let mods = store.resolve_mods(&mod_specs, update).await?;
 
-    let to_integrate = mod_specs
-        .iter()
-        .map(|u| mods[u].clone())
+    let resolved = resolve_dependencies(&mod_specs, &mods, policy)?;
+    resolved.log_added();
+
+    let to_integrate = resolved
+        .mods
+        .into_iter()
+        .map(|(_, info)| info)
         .collect::<Vec<_>>();
     let res_map: HashMap<ModResolution, ModSpecification> = mods
         .iter()
@@ -398,11 +430,15 @@ async fn integrate_async(
 
     let paths = store.fetch_mods_ordered(&urls, update, Some(tx)).await?;
 
-    tokio::task::spawn_blocking(|| {
-        crate::integrate::integrate(
+    let rules = rules.map_specs(|s| store.canonical_spec(s));
+
+    tokio::task::spawn_blocking(move || {
+        crate::integrate::integrate_to(
             fsd_pak,
+            &target,
             config,
             to_integrate.into_iter().zip(paths).collect(),
+            &rules,
         )
     })
     .await??;
@@ -421,6 +457,7 @@ impl LintMods {
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
+        conflict_rules: ConflictRules,
         enabled_lints: BTreeSet<LintId>,
         game_pak_path: Option<PathBuf>,
         tx: Sender<Message>,
@@ -436,10 +473,11 @@ impl LintMods {
 
             let report_res = match mod_path_pairs_res {
                 Ok(pairs) => tokio::task::spawn_blocking(move || {
-                    crate::mod_lints::run_lints(
+                    crate::mod_lints::run_lints_with_conflict_rules(
                         &enabled_lints,
                         pairs.into_iter().collect(),
                         game_pak_path,
+                        conflict_rules,
                     )
                 })
                 .await
@@ -467,8 +505,9 @@ impl LintMods {
     fn receive(self, app: &mut App) {
         if Some(self.rid) == app.lint_rid.as_ref().map(|r| r.rid) {
             match self.result {
-                Ok(report) => {
+                Ok(mut report) => {
                     info!("lint mod report complete");
+                    report.suppress(&app.state.mod_data.get_active_profile().lint_suppressions);
                     app.lint_report = Some(report);
                     app.last_action =
                         Some(LastAction::success("lint mod report complete".to_string()));
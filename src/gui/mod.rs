@@ -7,7 +7,7 @@ mod toggle_switch;
 //#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeMap;
 use std::ops::{Deref, RangeInclusive};
 use std::time::{Duration, Instant, SystemTime};
 use std::{
@@ -35,12 +35,18 @@ use tokio::{
 };
 use tracing::{debug, trace};
 
+use crate::dependencies::MissingDependencyPolicy;
 use crate::gui::find_string::searchable_text;
-use crate::mod_lints::{LintId, LintReport, SplitAssetPair};
+use crate::history::HistoryEntry;
+use crate::integrate::IntegrationTarget;
+use crate::mod_lints::{LintFactory, LintId, LintReport, Severity};
 use crate::providers::ProviderError;
+use crate::state::share::{is_share_code, ImportMode, SharedProfile};
 use crate::state::SortingConfig;
+use crate::watch::{ModWatcher, POLL_INTERVAL};
 use crate::Dirs;
 use crate::{
+    gc_cache,
     integrate::uninstall,
     is_drg_pak,
     providers::{
@@ -154,26 +160,18 @@ pub struct App {
     lint_report_window: Option<WindowLintReport>,
     lint_report: Option<LintReport>,
     lints_toggle_window: Option<WindowLintsToggle>,
-    lint_options: LintOptions,
+    history_window: Option<WindowHistory>,
+    /// Lints toggled on in the lint window, absent lints are off.
+    lint_options: BTreeMap<LintId, bool>,
     cache: CommonMarkCache,
     needs_restart: bool,
     self_update_rid: Option<MessageHandle<SelfUpdateProgress>>,
     original_exe_path: Option<PathBuf>,
     problematic_mod_id: Option<u32>,
-}
-
-#[derive(Default)]
-struct LintOptions {
-    archive_with_multiple_paks: bool,
-    archive_with_only_non_pak_files: bool,
-    asset_register_bin: bool,
-    conflicting: bool,
-    empty_archive: bool,
-    outdated_pak_version: bool,
-    shader_files: bool,
-    non_asset_files: bool,
-    split_asset_pairs: bool,
-    unmodified_game_assets: bool,
+    /// Watcher of the local mods of the active profile and when it was last polled.
+    watcher: Option<(ModWatcher, Instant)>,
+    /// Changes of local mods that triggered the running integration, if the watcher started it.
+    watch_changes: Option<String>,
 }
 
 struct LastAction {
@@ -246,15 +244,93 @@ impl App {
             lint_report_window: None,
             lint_report: None,
             lints_toggle_window: None,
-            lint_options: LintOptions::default(),
+            history_window: None,
+            lint_options: Default::default(),
             cache: Default::default(),
             needs_restart: false,
             self_update_rid: None,
             original_exe_path: None,
             problematic_mod_id: None,
+            watcher: None,
+            watch_changes: None,
         })
     }
 
+    fn active_mods(&self) -> Vec<ModSpecification> {
+        let active_profile = self.state.mod_data.active_profile.clone();
+        self.state
+            .mod_data
+            .enabled_mods_by_priority(&active_profile)
+            .into_iter()
+            .map(|mc| mc.spec)
+            .collect()
+    }
+
+    fn integrate_active_profile(&mut self, ctx: &egui::Context, watch_changes: Option<String>) {
+        let mods = self.active_mods();
+        self.last_action = None;
+        self.watch_changes = watch_changes;
+        self.integrate_rid = Some(message::Integrate::send(
+            &mut self.request_counter,
+            self.state.store.clone(),
+            mods,
+            self.state.config.missing_dependencies,
+            self.state
+                .mod_data
+                .get_active_profile()
+                .conflict_overrides
+                .clone(),
+            self.state.config.drg_pak_path.as_ref().unwrap().clone(),
+            IntegrationTarget {
+                // re-integrating on every save would push real bundles out of the history
+                history: self.watch_changes.is_none().then(|| self.state.history()),
+                ..Default::default()
+            },
+            self.state.config.deref().into(),
+            self.tx.clone(),
+            ctx.clone(),
+        ));
+        self.problematic_mod_id = None;
+    }
+
+    fn toggle_watch(&mut self, watch: bool) {
+        self.watcher = None;
+        if watch {
+            let watcher = ModWatcher::new(&self.active_mods(), Duration::from_secs(1));
+            if watcher.is_empty() {
+                self.last_action = Some(LastAction::failure(
+                    "Active profile does not contain any local mods to watch".to_string(),
+                ));
+            } else {
+                self.watcher = Some((watcher, Instant::now()));
+            }
+        }
+    }
+
+    /// Install mods when the watched local mods have changed.
+    fn poll_watcher(&mut self, ctx: &egui::Context) {
+        let Some((watcher, last_poll)) = &mut self.watcher else {
+            return;
+        };
+        ctx.request_repaint_after(POLL_INTERVAL);
+        if last_poll.elapsed() < POLL_INTERVAL || self.integrate_rid.is_some() {
+            return;
+        }
+        *last_poll = Instant::now();
+        if let Some(changes) = watcher.poll()
+            && !changes.is_empty()
+            && self.state.config.drg_pak_path.is_some()
+        {
+            let changes = changes
+                .iter()
+                .map(|change| change.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            debug!("local mods changed: {changes}");
+            self.integrate_active_profile(ctx, Some(changes));
+        }
+    }
+
     fn ui_profile(&mut self, ui: &mut Ui, profile: &str) {
         let sorting_config = self.get_sorting_config();
 
@@ -301,6 +377,7 @@ impl App {
                         ),
                     }
                 })
+                .map(|(i, spec)| (i, self.state.store.canonical_spec(&spec)))
                 .collect::<Vec<_>>();
 
             let ui_mod_tags = |ctx: &mut Ctx, ui: &mut Ui, info: &ModInfo| {
@@ -538,7 +615,7 @@ impl App {
                                 .range(RangeInclusive::new(-999, 999)),
                         )
                         .on_hover_text_at_pointer(
-                            "Load Priority\nIn case of asset conflict, mods with higher priority take precedent.\nCan have duplicate values.",
+                            "Load Priority\nIn case of asset conflict, mods with higher priority take precedent.\nMods with the same priority further down the list take precedent.",
                         );
                     });
 
@@ -551,9 +628,9 @@ impl App {
                     }
 
                     if mc.enabled {
-                        let is_duplicate = enabled_specs.iter().any(|(i, spec)| {
-                            Some(row_index) != *i && info.spec.satisfies_dependency(spec)
-                        });
+                        let is_duplicate = enabled_specs
+                            .iter()
+                            .any(|(i, spec)| Some(row_index) != *i && info.spec == *spec);
                         if is_duplicate
                             && ui
                                 .button(
@@ -570,7 +647,8 @@ impl App {
                             .suggested_dependencies
                             .iter()
                             .filter(|d| {
-                                !enabled_specs.iter().any(|(_, s)| s.satisfies_dependency(d))
+                                let d = self.state.store.canonical_spec(d);
+                                !enabled_specs.iter().any(|(_, s)| *s == d)
                             })
                             .collect::<Vec<_>>();
 
@@ -622,6 +700,18 @@ impl App {
                         "file" => {
                             ui.label("📁");
                         }
+                        "git" => {
+                            ui.label("🔀");
+                        }
+                        "directory" => {
+                            ui.label("📂");
+                        }
+                        "github" => {
+                            ui.label("🐙");
+                        }
+                        "index" => {
+                            ui.label("📇");
+                        }
                         _ => unimplemented!("unimplemented provider kind"),
                     }
 
@@ -800,6 +890,20 @@ impl App {
             .collect()
     }
 
+    /// Import the profile share code entered into the add mod box.
+    fn import_profile(&mut self) {
+        self.last_action = Some(match SharedProfile::parse(&self.resolve_mod) {
+            Ok(shared) => {
+                let name = shared.import(&mut self.state.mod_data, ImportMode::Rename);
+                self.state.mod_data.active_profile = name.clone();
+                self.state.mod_data.save().unwrap();
+                self.resolve_mod.clear();
+                LastAction::success(format!("Imported profile \"{name}\""))
+            }
+            Err(e) => LastAction::failure(format!("Failed to import profile: {e}")),
+        });
+    }
+
     fn build_mod_string(mods: &Vec<ModConfig>) -> String {
         let mut string = String::new();
         for m in mods {
@@ -1083,6 +1187,56 @@ impl App {
                         });
                         ui.end_row();
 
+                        ui.label("Missing dependencies:");
+                        let config = &mut self.state.config;
+                        let old_policy = config.missing_dependencies;
+                        egui::ComboBox::from_id_salt("missing-dependencies")
+                            .selected_text(old_policy.description())
+                            .show_ui(ui, |ui| {
+                                for policy in MissingDependencyPolicy::ALL {
+                                    ui.selectable_value(
+                                        &mut config.missing_dependencies,
+                                        policy,
+                                        policy.description(),
+                                    );
+                                }
+                            });
+                        if config.missing_dependencies != old_policy {
+                            config.save().unwrap();
+                        }
+                        ui.end_row();
+
+                        ui.label("Offline mode:");
+                        if ui
+                            .checkbox(&mut self.state.config.offline, "")
+                            .on_hover_text("Only use cached mods and metadata, never access the network")
+                            .changed()
+                        {
+                            self.state.store.set_offline(self.state.config.offline);
+                            self.state.config.save().unwrap();
+                        }
+                        ui.end_row();
+
+                        ui.label("Mod cache:");
+                        if ui
+                            .button("Remove unused files")
+                            .on_hover_text("Deletes cached mod files not used by any profile")
+                            .clicked()
+                        {
+                            self.last_action = Some(match gc_cache(&self.state, 0, false) {
+                                Ok(stats) => LastAction::success(stats.to_string()),
+                                Err(MintError::ProviderError {
+                                    source: ProviderError::NoProvider { factory, .. },
+                                }) => {
+                                    self.window_provider_parameters =
+                                        Some(WindowProviderParameters::new(factory, &self.state));
+                                    LastAction::failure("no provider".to_string())
+                                }
+                                Err(e) => LastAction::failure(e.to_string()),
+                            });
+                        }
+                        ui.end_row();
+
                         ui.label("Mod providers:");
                         ui.end_row();
 
@@ -1138,55 +1292,22 @@ impl App {
                             ui.heading("Enabled?");
                             ui.end_row();
 
-                            ui.label("Archive with multiple paks");
-                            ui.add(toggle_switch(
-                                &mut self.lint_options.archive_with_multiple_paks,
-                            ));
-                            ui.end_row();
-
-                            ui.label("Archive with only non-pak files");
-                            ui.add(toggle_switch(
-                                &mut self.lint_options.archive_with_only_non_pak_files,
-                            ));
-                            ui.end_row();
-
-                            ui.label("Mods containing AssetRegister.bin");
-                            ui.add(toggle_switch(&mut self.lint_options.asset_register_bin));
-                            ui.end_row();
-
-                            ui.label("Mods containing conflicting files");
-                            ui.add(toggle_switch(&mut self.lint_options.conflicting));
-                            ui.end_row();
-
-                            ui.label("Mods containing empty archives");
-                            ui.add(toggle_switch(&mut self.lint_options.empty_archive));
-                            ui.end_row();
-
-                            ui.label("Mods containing oudated pak version");
-                            ui.add(toggle_switch(&mut self.lint_options.outdated_pak_version));
-                            ui.end_row();
-
-                            ui.label("Mods containing shader files");
-                            ui.add(toggle_switch(&mut self.lint_options.shader_files));
-                            ui.end_row();
-
-                            ui.label("Mods containing non-asset files");
-                            ui.add(toggle_switch(&mut self.lint_options.non_asset_files));
-                            ui.end_row();
-
-                            ui.label("Mods containing split {uexp, uasset} pairs");
-                            ui.add(toggle_switch(&mut self.lint_options.split_asset_pairs));
-                            ui.end_row();
-
-                            ui.label("Mods containing unmodified game assets");
-                            ui.add_enabled(
-                                self.state.config.drg_pak_path.is_some(),
-                                toggle_switch(&mut self.lint_options.unmodified_game_assets),
-                            )
-                            .on_disabled_hover_text(
-                                "This lint requires DRG pak path to be specified",
-                            );
-                            ui.end_row();
+                            for lint in LintFactory::all() {
+                                ui.label(lint.description);
+                                let enabled = self.lint_options.entry(lint.id).or_default();
+                                if lint.requires_game_pak {
+                                    ui.add_enabled(
+                                        self.state.config.drg_pak_path.is_some(),
+                                        toggle_switch(enabled),
+                                    )
+                                    .on_disabled_hover_text(
+                                        "This lint requires DRG pak path to be specified",
+                                    );
+                                } else {
+                                    ui.add(toggle_switch(enabled));
+                                }
+                                ui.end_row();
+                            }
                         });
                     });
 
@@ -1204,57 +1325,30 @@ impl App {
                             )
                             .clicked()
                         {
-                            let lint_options = BTreeMap::from([
-                                (
-                                    LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
-                                    self.lint_options.archive_with_multiple_paks,
-                                ),
-                                (
-                                    LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
-                                    self.lint_options.archive_with_only_non_pak_files,
-                                ),
-                                (
-                                    LintId::ASSET_REGISTRY_BIN,
-                                    self.lint_options.asset_register_bin,
-                                ),
-                                (LintId::CONFLICTING, self.lint_options.conflicting),
-                                (LintId::EMPTY_ARCHIVE, self.lint_options.empty_archive),
-                                (
-                                    LintId::OUTDATED_PAK_VERSION,
-                                    self.lint_options.outdated_pak_version,
-                                ),
-                                (LintId::SHADER_FILES, self.lint_options.shader_files),
-                                (LintId::NON_ASSET_FILES, self.lint_options.non_asset_files),
-                                (
-                                    LintId::SPLIT_ASSET_PAIRS,
-                                    self.lint_options.split_asset_pairs,
-                                ),
-                                (
-                                    LintId::UNMODIFIED_GAME_ASSETS,
-                                    self.lint_options.unmodified_game_assets,
-                                ),
-                            ]);
+                            trace!(lint_options = ?self.lint_options);
 
-                            trace!(?lint_options);
-
-                            let mut mods = Vec::new();
-                            self.state.mod_data.for_each_enabled_mod(
-                                &self.state.mod_data.active_profile,
-                                |mc| {
-                                    mods.push(mc.spec.clone());
-                                },
-                            );
+                            let mods = self
+                                .state
+                                .mod_data
+                                .enabled_mods_by_priority(&self.state.mod_data.active_profile)
+                                .into_iter()
+                                .map(|mc| mc.spec)
+                                .collect();
 
                             self.lint_report = None;
                             self.lint_rid = Some(message::LintMods::send(
                                 &mut self.request_counter,
                                 self.state.store.clone(),
                                 mods,
-                                BTreeSet::from_iter(
-                                    lint_options
-                                        .into_iter()
-                                        .filter_map(|(lint, enabled)| enabled.then_some(lint)),
-                                ),
+                                self.state
+                                    .mod_data
+                                    .get_active_profile()
+                                    .conflict_overrides
+                                    .clone(),
+                                self.lint_options
+                                    .iter()
+                                    .filter_map(|(lint, enabled)| enabled.then_some(*lint))
+                                    .collect(),
                                 self.state.config.drg_pak_path.clone(),
                                 self.tx.clone(),
                                 ctx.clone(),
@@ -1287,274 +1381,50 @@ impl App {
                             .show(ui, |ui| {
                                 const AMBER: Color32 = Color32::from_rgb(255, 191, 0);
 
-                                if let Some(conflicting_mods) = &report.conflicting_mods {
-                                    if !conflicting_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new("⚠ Mods(s) with conflicting asset modifications detected")
-                                                .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            conflicting_mods.iter().for_each(|(path, mods)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ Conflicting modification of asset `{}`",
-                                                        path
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(
-                                                    ui,
-                                                    |ui| {
-                                                        mods.iter().for_each(|mod_spec| {
-                                                            ui.label(&mod_spec.url);
-                                                        });
-                                                    },
-                                                );
-                                            });
-                                        });
+                                for (lint, output) in report.iter() {
+                                    let findings = output.findings(lint);
+                                    if findings.is_empty() {
+                                        continue;
                                     }
-                                }
-
-                                if let Some(asset_register_bin_mods) = &report.asset_register_bin_mods {
-                                    if !asset_register_bin_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new("ℹ Mod(s) with `AssetRegistry.bin` included detected")
-                                                .color(Color32::LIGHT_BLUE),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            asset_register_bin_mods.iter().for_each(
-                                                |(r#mod, paths)| {
-                                                    CollapsingHeader::new(
-                                                        RichText::new(format!(
-                                                        "ℹ {} includes one or more `AssetRegistry.bin`",
-                                                        r#mod.url
-                                                    ))
-                                                        .color(Color32::LIGHT_BLUE),
-                                                    )
+                                    let (icon, color) = match lint.default_severity {
+                                        Severity::Info => ("ℹ", Color32::LIGHT_BLUE),
+                                        Severity::Warning => ("⚠", AMBER),
+                                        Severity::Error => ("⚠", Color32::RED),
+                                    };
+                                    CollapsingHeader::new(
+                                        RichText::new(format!(
+                                            "{icon} {} detected",
+                                            lint.description
+                                        ))
+                                        .color(color),
+                                    )
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        for (i, finding) in findings.iter().enumerate() {
+                                            let text = RichText::new(format!(
+                                                "{icon} {}: {}",
+                                                finding
+                                                    .mods
+                                                    .iter()
+                                                    .map(|m| m.url.as_str())
+                                                    .collect::<Vec<_>>()
+                                                    .join(", "),
+                                                finding.message
+                                            ))
+                                            .color(color);
+                                            if finding.paths.is_empty() {
+                                                ui.label(text);
+                                            } else {
+                                                CollapsingHeader::new(text)
+                                                    .id_salt((lint.id, i))
                                                     .show(ui, |ui| {
-                                                        paths.iter().for_each(|path| {
+                                                        for path in &finding.paths {
                                                             ui.label(path);
-                                                        });
-                                                    });
-                                                },
-                                            );
-                                        });
-                                    }
-                                }
-
-                                if let Some(shader_file_mods) = &report.shader_file_mods {
-                                    if !shader_file_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mods(s) with shader files included detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            shader_file_mods.iter().for_each(
-                                                |(r#mod, shader_files)| {
-                                                    CollapsingHeader::new(
-                                                        RichText::new(format!(
-                                                            "⚠ {} includes one or more shader files",
-                                                            r#mod.url
-                                                        ))
-                                                        .color(AMBER),
-                                                    )
-                                                    .show(ui, |ui| {
-                                                        shader_files.iter().for_each(|shader_file| {
-                                                            ui.label(shader_file);
-                                                        });
-                                                    });
-                                                },
-                                            );
-                                        });
-                                    }
-                                }
-
-                                if let Some(outdated_pak_version_mods) = &report.outdated_pak_version_mods {
-                                    if !outdated_pak_version_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with outdated pak version detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            outdated_pak_version_mods.iter().for_each(
-                                                |(r#mod, version)| {
-                                                    ui.label(
-                                                        RichText::new(format!(
-                                                            "⚠ {} includes outdated pak version {}",
-                                                            r#mod.url, version
-                                                        ))
-                                                        .color(AMBER),
-                                                    );
-                                                },
-                                            );
-                                        });
-                                    }
-                                }
-
-                                if let Some(empty_archive_mods) = &report.empty_archive_mods {
-                                    if !empty_archive_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with empty archives detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            empty_archive_mods.iter().for_each(|r#mod| {
-                                                ui.label(
-                                                    RichText::new(format!(
-                                                        "⚠ {} contains an empty archive",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                );
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(archive_with_only_non_pak_files_mods) = &report.archive_with_only_non_pak_files_mods {
-                                    if !archive_with_only_non_pak_files_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with only non-`.pak` files detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            archive_with_only_non_pak_files_mods.iter().for_each(|r#mod| {
-                                                ui.label(
-                                                    RichText::new(format!(
-                                                        "⚠ {} contains only non-`.pak` files, perhaps the author forgot to pack it?",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                );
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(archive_with_multiple_paks_mods) = &report.archive_with_multiple_paks_mods {
-                                    if !archive_with_multiple_paks_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with multiple `.pak`s detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            archive_with_multiple_paks_mods.iter().for_each(|r#mod| {
-                                                ui.label(RichText::new(format!(
-                                                    "⚠ {} contains multiple `.pak`s, only the first encountered `.pak` will be loaded",
-                                                    r#mod.url
-                                                ))
-                                                .color(AMBER));
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(non_asset_file_mods) = &report.non_asset_file_mods {
-                                    if !non_asset_file_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with non-asset files detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            non_asset_file_mods.iter().for_each(|(r#mod, files)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ {} includes non-asset files",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(ui, |ui| {
-                                                    files.iter().for_each(|file| {
-                                                        ui.label(file);
-                                                    });
-                                                });
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(split_asset_pairs_mods) = &report.split_asset_pairs_mods {
-                                    if !split_asset_pairs_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with split {uexp, uasset} pairs detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            split_asset_pairs_mods.iter().for_each(|(r#mod, files)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ {} includes split {{uexp, uasset}} pairs",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(ui, |ui| {
-                                                    files.iter().for_each(|(file, kind)| {
-                                                        match kind {
-                                                            SplitAssetPair::MissingUasset => {
-                                                                ui.label(format!("`{file}` missing matching .uasset file"));
-                                                            },
-                                                            SplitAssetPair::MissingUexp => {
-                                                                ui.label(format!("`{file}` missing matching .uexp file"));
-                                                            }
                                                         }
                                                     });
-                                                });
-                                            });
-                                        });
-                                    }
-                                }
-
-                                if let Some(unmodified_game_assets_mods) = &report.unmodified_game_assets_mods {
-                                    if !unmodified_game_assets_mods.is_empty() {
-                                        CollapsingHeader::new(
-                                            RichText::new(
-                                                "⚠ Mod(s) with unmodified game assets detected",
-                                            )
-                                            .color(AMBER),
-                                        )
-                                        .default_open(true)
-                                        .show(ui, |ui| {
-                                            unmodified_game_assets_mods.iter().for_each(|(r#mod, files)| {
-                                                CollapsingHeader::new(
-                                                    RichText::new(format!(
-                                                        "⚠ {} includes unmodified game assets",
-                                                        r#mod.url
-                                                    ))
-                                                    .color(AMBER),
-                                                )
-                                                .show(ui, |ui| {
-                                                    files.iter().for_each(|file| {
-                                                        ui.label(file);
-                                                    });
-                                                });
-                                            });
-                                        });
-                                    }
+                                            }
+                                        }
+                                    });
                                 }
                             });
                     } else {
@@ -1570,6 +1440,82 @@ impl App {
         }
     }
 
+    fn show_history(&mut self, ctx: &egui::Context) {
+        if let Some(window) = &self.history_window {
+            let mut open = true;
+            let mut to_restore = None;
+
+            egui::Window::new("Bundle history")
+                .open(&mut open)
+                .resizable(true)
+                .show(ctx, |ui| match &window.entries {
+                    Ok(entries) if entries.is_empty() => {
+                        ui.label("No bundles kept yet, they are added on every install.");
+                    }
+                    Ok(entries) => {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for (i, entry) in entries.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let label = if i == 0 {
+                                        format!("{} (latest)", entry.age())
+                                    } else {
+                                        entry.age()
+                                    };
+                                    CollapsingHeader::new(format!(
+                                        "{label}: {} mods",
+                                        entry.mods.len()
+                                    ))
+                                    .id_salt(("history", i))
+                                    .show(ui, |ui| {
+                                        for m in &entry.mods {
+                                            ui.label(&m.name).on_hover_text(&m.url);
+                                        }
+                                    });
+                                    if ui
+                                        .add_enabled(
+                                            self.state.config.drg_pak_path.is_some(),
+                                            Button::new("Restore"),
+                                        )
+                                        .on_hover_text(
+                                            "Replace the installed mods with this bundle",
+                                        )
+                                        .clicked()
+                                    {
+                                        to_restore = Some(entry.clone());
+                                    }
+                                });
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                    }
+                });
+
+            if let Some(entry) = to_restore
+                && let Some(pak_path) = &self.state.config.drg_pak_path
+            {
+                let result = mint_lib::DRGInstallation::from_pak_path(pak_path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|installation| {
+                        self.state
+                            .history()
+                            .restore(&entry, installation.paks_path().join("mods_P.pak"))
+                            .map_err(|e| e.to_string())
+                    });
+                self.last_action = Some(match result {
+                    Ok(()) => {
+                        LastAction::success(format!("restored bundle integrated {}", entry.age()))
+                    }
+                    Err(e) => LastAction::failure(format!("failed to restore bundle: {e}")),
+                });
+            }
+            if !open {
+                self.history_window = None;
+            }
+        }
+    }
+
     fn get_sorting_config(&self) -> Option<SortingConfig> {
         self.state.config.sorting_config.clone()
     }
@@ -1688,6 +1634,24 @@ impl WindowSettings {
 
 struct WindowLintReport;
 
+struct WindowHistory {
+    entries: Result<Vec<HistoryEntry>, String>,
+}
+
+impl WindowHistory {
+    fn new(state: &State) -> Self {
+        let mut window = Self {
+            entries: Ok(vec![]),
+        };
+        window.refresh(state);
+        window
+    }
+
+    fn refresh(&mut self, state: &State) {
+        self.entries = state.history().list().map_err(|e| e.to_string());
+    }
+}
+
 struct WindowLintsToggle;
 
 impl eframe::App for App {
@@ -1722,6 +1686,8 @@ impl eframe::App for App {
             msg.handle(self);
         }
 
+        self.poll_watcher(ctx);
+
         // begin draw
 
         self.show_update_window(ctx);
@@ -1730,6 +1696,7 @@ impl eframe::App for App {
         self.show_settings(ctx);
         self.show_lints_toggle(ctx);
         self.show_lint_report(ctx);
+        self.show_history(ctx);
 
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.with_layout(egui::Layout::right_to_left(Align::TOP), |ui| {
@@ -1772,32 +1739,18 @@ impl eframe::App for App {
                             }
 
                             if button.clicked() {
-                                let mut mod_configs = Vec::new();
-                                let mut mods = Vec::new();
-                                let active_profile = self.state.mod_data.active_profile.clone();
-                                self.state
-                                    .mod_data
-                                    .for_each_enabled_mod(&active_profile, |mc| {
-                                        mod_configs.push(mc.clone());
-                                    });
-
-                                mod_configs.sort_by_key(|k| -k.priority);
-
-                                for config in mod_configs {
-                                    mods.push(config.spec.clone());
-                                }
+                                self.integrate_active_profile(ctx, None);
+                            }
 
-                                self.last_action = None;
-                                self.integrate_rid = Some(message::Integrate::send(
-                                    &mut self.request_counter,
-                                    self.state.store.clone(),
-                                    mods,
-                                    self.state.config.drg_pak_path.as_ref().unwrap().clone(),
-                                    self.state.config.deref().into(),
-                                    self.tx.clone(),
-                                    ctx.clone(),
-                                ));
-                                self.problematic_mod_id = None;
+                            let mut watch = self.watcher.is_some();
+                            if ui
+                                .checkbox(&mut watch, "Watch")
+                                .on_hover_text(
+                                    "Install mods again whenever a local mod file or directory of the active profile changes",
+                                )
+                                .changed()
+                            {
+                                self.toggle_watch(watch);
                             }
                         });
 
@@ -1871,6 +1824,13 @@ impl eframe::App for App {
                 {
                     self.lints_toggle_window = Some(WindowLintsToggle);
                 }
+                if ui
+                    .button("History")
+                    .on_hover_text("Restore a previously installed mod bundle")
+                    .clicked()
+                {
+                    self.history_window = Some(WindowHistory::new(&self.state));
+                }
                 if ui.button("⚙").on_hover_text("Open settings").clicked() {
                     self.settings_window = Some(WindowSettings::new(&self.state));
                 }
@@ -1938,6 +1898,17 @@ impl eframe::App for App {
                     let mods = Self::build_mod_string(&mods);
                     ui.output_mut(|o| o.copied_text = mods);
                 }
+                if ui
+                    .button("🔗")
+                    .on_hover_text_at_pointer(
+                        "Copy profile share code\nPaste it into \"Add mod...\" to import the profile",
+                    )
+                    .clicked()
+                {
+                    if let Ok(shared) = SharedProfile::export(mod_data, &mod_data.active_profile) {
+                        ui.output_mut(|o| o.copied_text = shared.to_share_code());
+                    }
+                }
 
                 // TODO find better icon, flesh out multiple-view usage, fix GUI locking
                 /*
@@ -1987,8 +1958,12 @@ impl eframe::App for App {
                             .hint_text("Add mod..."),
                     );
                     if is_committed(&resolve) {
-                        message::ResolveMods::send(self, ctx, self.parse_mods(), false);
-                        self.problematic_mod_id = None;
+                        if is_share_code(&self.resolve_mod) {
+                            self.import_profile();
+                        } else {
+                            message::ResolveMods::send(self, ctx, self.parse_mods(), false);
+                            self.problematic_mod_id = None;
+                        }
                     }
                 });
             });
@@ -0,0 +1,238 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::providers::ModInfo;
+use crate::temp_file_beside;
+
+/// Number of previous mod bundles kept by default.
+pub const DEFAULT_KEEP: usize = 5;
+
+const BUNDLE_FILE: &str = "mods_P.pak";
+const ENTRY_FILE: &str = "entry.json";
+
+#[derive(Debug, Snafu)]
+pub enum HistoryError {
+    #[snafu(display("failed to access bundle history at {}", path.display()))]
+    HistoryIo { source: io::Error, path: PathBuf },
+    #[snafu(display("failed to parse bundle history entry {}", path.display()))]
+    InvalidEntry {
+        source: serde_json::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("bundle history contains {len} bundles, cannot go back {n}"))]
+    NoSuchEntry { n: usize, len: usize },
+}
+
+/// A mod contained in a bundle of the history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryMod {
+    pub name: String,
+    pub url: String,
+}
+
+/// A previously integrated mod bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    #[serde(skip)]
+    dir: PathBuf,
+    /// When the bundle was integrated, in seconds since the Unix epoch.
+    pub created: u64,
+    pub mods: Vec<HistoryMod>,
+}
+
+impl HistoryEntry {
+    pub fn bundle_path(&self) -> PathBuf {
+        self.dir.join(BUNDLE_FILE)
+    }
+
+    /// Rough time since the bundle was integrated, e.g. "3 hours ago".
+    pub fn age(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let age = now
+            .saturating_sub(Duration::from_secs(self.created))
+            .as_secs();
+        let (n, unit) = match age {
+            0..60 => return "just now".to_string(),
+            60..3600 => (age / 60, "minute"),
+            3600..86400 => (age / 3600, "hour"),
+            _ => (age / 86400, "day"),
+        };
+        format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" })
+    }
+}
+
+/// Copies of the last integrated mod bundles in the data directory so a bad update can be rolled
+/// back. Every bundle is kept in its own directory together with the list of mods it contains.
+#[derive(Debug, Clone)]
+pub struct BundleHistory {
+    dir: PathBuf,
+    keep: usize,
+}
+
+impl BundleHistory {
+    /// History in `data_dir` keeping the last `keep` bundles. Nothing is recorded if `keep` is 0.
+    pub fn new<P: AsRef<Path>>(data_dir: P, keep: usize) -> Self {
+        Self {
+            dir: data_dir.as_ref().join("history"),
+            keep,
+        }
+    }
+
+    /// Recorded bundles, newest first.
+    pub fn list(&self) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let path = &self.dir;
+        let read_dir = match fs::read_dir(path) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e).context(HistoryIoSnafu { path }),
+        };
+        let mut entries = vec![];
+        for dir_entry in read_dir {
+            let dir_entry = dir_entry.context(HistoryIoSnafu { path })?;
+            // entries being recorded are assembled in hidden temporary directories
+            if dir_entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = dir_entry.path().join(ENTRY_FILE);
+            let buf = match fs::read(&path) {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).context(HistoryIoSnafu { path }),
+            };
+            let mut entry =
+                serde_json::from_slice::<HistoryEntry>(&buf).context(InvalidEntrySnafu { path })?;
+            entry.dir = dir_entry.path();
+            entries.push(entry);
+        }
+        entries.sort_by(|a, b| b.dir.cmp(&a.dir));
+        Ok(entries)
+    }
+
+    /// Bundle of `n` integrations ago, 0 being the latest.
+    pub fn get(&self, n: usize) -> Result<HistoryEntry, HistoryError> {
+        let entries = self.list()?;
+        let len = entries.len();
+        entries
+            .into_iter()
+            .nth(n)
+            .context(NoSuchEntrySnafu { n, len })
+    }
+
+    /// Keep a copy of the bundle at `path_mod_pak` containing `mods` and delete the oldest
+    /// bundles beyond the number to keep.
+    pub fn record<P: AsRef<Path>>(
+        &self,
+        path_mod_pak: P,
+        mods: &[(ModInfo, PathBuf)],
+    ) -> Result<(), HistoryError> {
+        if self.keep == 0 {
+            return Ok(());
+        }
+        let path = &self.dir;
+        fs::create_dir_all(path).context(HistoryIoSnafu { path })?;
+
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let entry = HistoryEntry {
+            dir: PathBuf::new(),
+            created: created.as_secs(),
+            mods: mods
+                .iter()
+                .map(|(info, _)| HistoryMod {
+                    name: info.name.clone(),
+                    url: info.resolution.get_resolvable_url_or_name().to_string(),
+                })
+                .collect(),
+        };
+
+        // assembled in a temporary directory and renamed so an interrupted copy is never listed
+        let temp_dir = tempfile::Builder::new()
+            .prefix(".tmp")
+            .tempdir_in(path)
+            .context(HistoryIoSnafu { path })?;
+        fs::copy(path_mod_pak.as_ref(), temp_dir.path().join(BUNDLE_FILE))
+            .context(HistoryIoSnafu { path })?;
+        fs::write(
+            temp_dir.path().join(ENTRY_FILE),
+            serde_json::to_vec_pretty(&entry).unwrap(),
+        )
+        .context(HistoryIoSnafu { path })?;
+
+        // zero padded so entries sort by name in the order they were recorded
+        let mut id = created.as_millis();
+        let entry_dir = loop {
+            let entry_dir = path.join(format!("{id:016}"));
+            if !entry_dir.exists() {
+                break entry_dir;
+            }
+            id += 1;
+        };
+        fs::rename(temp_dir.path(), &entry_dir).context(HistoryIoSnafu { path: &entry_dir })?;
+
+        for old in self.list()?.into_iter().skip(self.keep) {
+            fs::remove_dir_all(&old.dir).context(HistoryIoSnafu { path: &old.dir })?;
+        }
+        Ok(())
+    }
+
+    /// Replace the bundle at `path_mod_pak` with the one of `entry`.
+    pub fn restore<P: AsRef<Path>>(
+        &self,
+        entry: &HistoryEntry,
+        path_mod_pak: P,
+    ) -> Result<(), HistoryError> {
+        let path = path_mod_pak.as_ref();
+        let mut temp_file = temp_file_beside(path).context(HistoryIoSnafu { path })?;
+        io::copy(
+            &mut fs::File::open(entry.bundle_path())
+                .context(HistoryIoSnafu { path: &entry.dir })?,
+            temp_file.as_file_mut(),
+        )
+        .context(HistoryIoSnafu { path })?;
+        temp_file
+            .persist(path)
+            .map_err(|e| e.error)
+            .context(HistoryIoSnafu { path })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = BundleHistory::new(dir.path(), 2);
+        let pak = dir.path().join("mods_P.pak");
+
+        for contents in ["a", "b", "c"] {
+            fs::write(&pak, contents).unwrap();
+            history.record(&pak, &[]).unwrap();
+        }
+        let entries = history.list().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(fs::read(entries[0].bundle_path()).unwrap(), b"c");
+        assert_eq!(fs::read(entries[1].bundle_path()).unwrap(), b"b");
+
+        history.restore(&history.get(1).unwrap(), &pak).unwrap();
+        assert_eq!(fs::read(&pak).unwrap(), b"b");
+        assert!(matches!(
+            history.get(2),
+            Err(HistoryError::NoSuchEntry { n: 2, len: 2 })
+        ));
+
+        let disabled = BundleHistory::new(dir.path().join("disabled"), 0);
+        disabled.record(&pak, &[]).unwrap();
+        assert!(disabled.list().unwrap().is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{BufReader, BufWriter, Cursor, ErrorKind, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 
@@ -7,7 +7,7 @@ use fs_err as fs;
 use repak::PakWriter;
 use serde::Deserialize;
 use snafu::{prelude::*, Whatever};
-use tracing::info;
+use tracing::{info, warn};
 use uasset_utils::asset_registry::{AssetRegistry, Readable as _, Writable as _};
 use uasset_utils::paths::{PakPath, PakPathBuf, PakPathComponentTrait};
 use uasset_utils::splice::{
@@ -16,9 +16,16 @@ use uasset_utils::splice::{
 use unreal_asset::engine_version::EngineVersion;
 use unreal_asset::AssetBuilder;
 
+use crate::conflicts::{find_conflicts, log_conflicts, ConflictReport, ConflictRules};
+use crate::dependencies::DependencyError;
+use crate::history::BundleHistory;
+use crate::lockfile::LockfileError;
 use crate::mod_lints::LintError;
 use crate::providers::{ModInfo, ProviderError, ReadSeek};
-use mint_lib::mod_info::{ApprovalStatus, Meta, MetaConfig, MetaMod, SemverVersion};
+use crate::temp_file_beside;
+use mint_lib::mod_info::{
+    ApprovalStatus, Meta, MetaConfig, MetaMod, SemverVersion, META_FORMAT_VERSION,
+};
 use mint_lib::DRGInstallation;
 
 use unreal_asset::{
@@ -63,7 +70,38 @@ pub fn uninstall<P: AsRef<Path>>(path_pak: P, modio_mods: HashSet<u32>) -> Resul
         }
         .with_whatever_context(|_| format!("failed to remove {}", path_hook_dll.display()))?;
     }
-    uninstall_modio(&installation, modio_mods).ok();
+    if let Err(e) = uninstall_modio(&installation, modio_mods) {
+        warn!("failed to update mod.io settings: {e}");
+    }
+    Ok(())
+}
+
+fn game_user_settings_path(installation: &DRGInstallation) -> PathBuf {
+    installation
+        .root
+        .join("Saved/Config/WindowsNoEditor/GameUserSettings.ini")
+}
+
+/// Copy of GameUserSettings.ini made by [`uninstall`] before it changes which mod.io mods are
+/// enabled.
+fn game_user_settings_backup_path(installation: &DRGInstallation) -> PathBuf {
+    game_user_settings_path(installation).with_extension("ini.mint-backup")
+}
+
+/// Restore the GameUserSettings.ini backed up by the last [`uninstall`], undoing its changes to
+/// the official integration's mod settings.
+#[tracing::instrument(level = "debug", skip(path_pak))]
+pub fn restore_uninstall_backup<P: AsRef<Path>>(path_pak: P) -> Result<(), Whatever> {
+    let installation = DRGInstallation::from_pak_path(path_pak)
+        .whatever_context("failed to get DRG installation")?;
+    let backup_path = game_user_settings_backup_path(&installation);
+    ensure_whatever!(
+        backup_path.exists(),
+        "no GameUserSettings.ini backup found at {}",
+        backup_path.display()
+    );
+    fs::copy(&backup_path, game_user_settings_path(&installation))
+        .whatever_context("failed to restore GameUserSettings.ini")?;
     Ok(())
 }
 
@@ -90,11 +128,11 @@ fn uninstall_modio(
             .whatever_context("failed to read mod.io metadata/state.json")?,
     ))
     .whatever_context("failed to parse mod.io metadata/state.json")?;
-    let config_path = installation
-        .root
-        .join("Saved/Config/WindowsNoEditor/GameUserSettings.ini");
+    let config_path = game_user_settings_path(installation);
     let mut config = ini::Ini::load_from_file(&config_path)
         .whatever_context("failed to load GameUserSettings.ini")?;
+    fs::copy(&config_path, game_user_settings_backup_path(installation))
+        .whatever_context("failed to back up GameUserSettings.ini")?;
 
     let ignore_keys = HashSet::from(["CurrentModioUserId"]);
 
@@ -151,6 +189,38 @@ fn uninstall_modio(
 static INTEGRATION_DIR: include_dir::Dir<'_> =
     include_dir::include_dir!("$CARGO_MANIFEST_DIR/assets/integration");
 
+/// Paths of the files of [`INTEGRATION_DIR`], mod files at these paths are replaced by mint's own.
+pub(crate) fn integration_file_paths() -> impl Iterator<Item = String> {
+    let mut int_files = HashMap::new();
+    collect_dir_files(&INTEGRATION_DIR, &mut int_files);
+    int_files.into_keys()
+}
+
+/// Game assets hooked to load mint.
+pub(crate) const PCB_PATH: &str = "FSD/Content/Game/BP_PlayerControllerBase";
+/// Game assets patched by [`patch`].
+pub(crate) const PATCH_PATHS: [&str; 6] = [
+    "FSD/Content/Game/BP_GameInstance",
+    "FSD/Content/Game/SpaceRig/BP_PlayerController_SpaceRig",
+    "FSD/Content/Game/StartMenu/Bp_StartMenu_PlayerController",
+    "FSD/Content/UI/Menu_DeepDives/ITM_DeepDives_Join",
+    "FSD/Content/UI/Menu_ServerList/_MENU_ServerList",
+    "FSD/Content/UI/Menu_ServerList/WND_JoiningModded",
+];
+const ESCAPE_MENU_PATH: &str = "FSD/Content/UI/Menu_EscapeMenu/MENU_EscapeMenu";
+const MODDING_TAB_PATH: &str = "FSD/Content/UI/Menu_EscapeMenu/Modding/MENU_Modding";
+const SERVER_LIST_ENTRY_PATH: &str = "FSD/Content/UI/Menu_ServerList/ITM_ServerList_Entry";
+
+/// All game assets read from the game pak (or a mod overriding them) and patched before being
+/// written to the bundle.
+pub(crate) fn deferred_asset_paths() -> impl Iterator<Item = &'static str> {
+    [PCB_PATH].into_iter().chain(PATCH_PATHS).chain([
+        ESCAPE_MENU_PATH,
+        MODDING_TAB_PATH,
+        SERVER_LIST_ENTRY_PATH,
+    ])
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
 pub enum IntegrationError {
@@ -197,6 +267,10 @@ pub enum IntegrationError {
     JoinError { source: tokio::task::JoinError },
     #[snafu(transparent)]
     LintError { source: LintError },
+    #[snafu(transparent)]
+    LockfileError { source: LockfileError },
+    #[snafu(transparent)]
+    DependencyError { source: DependencyError },
     #[snafu(display("self update failed: {source:?}"))]
     SelfUpdateFailed {
         source: Box<dyn std::error::Error + Send + Sync>,
@@ -216,19 +290,146 @@ impl IntegrationError {
     }
 }
 
+/// Where [`integrate_to`] writes the mod bundle and whether it touches the game installation.
+#[derive(Debug, Clone)]
+pub struct IntegrationTarget {
+    /// Output path of the bundle. If `None`, `mods_P.pak` in the installation's paks directory is
+    /// used.
+    pub output: Option<PathBuf>,
+    /// Whether to install the hook DLL into the installation's binaries directory.
+    pub install_hook: bool,
+    /// If set, the conflict report is additionally written to this path as JSON.
+    pub conflict_report: Option<PathBuf>,
+    /// If set, a copy of the bundle is kept in this history so it can be rolled back to.
+    pub history: Option<BundleHistory>,
+}
+
+impl Default for IntegrationTarget {
+    fn default() -> Self {
+        Self {
+            output: None,
+            install_hook: true,
+            conflict_report: None,
+            history: None,
+        }
+    }
+}
+
 #[tracing::instrument(skip_all)]
 pub fn integrate<P: AsRef<Path>>(
     path_pak: P,
     config: MetaConfig,
     mods: Vec<(ModInfo, PathBuf)>,
 ) -> Result<(), IntegrationError> {
-    let Ok(installation) = DRGInstallation::from_pak_path(&path_pak) else {
-        return Err(IntegrationError::DrgInstallationNotFound {
-            path: path_pak.as_ref().to_path_buf(),
-        });
+    integrate_to(
+        path_pak,
+        &IntegrationTarget::default(),
+        config,
+        mods,
+        &ConflictRules::default(),
+    )
+}
+
+#[tracing::instrument(skip_all)]
+pub fn integrate_to<P: AsRef<Path>>(
+    path_pak: P,
+    target: &IntegrationTarget,
+    config: MetaConfig,
+    mods: Vec<(ModInfo, PathBuf)>,
+    rules: &ConflictRules,
+) -> Result<(), IntegrationError> {
+    install_bundle(path_pak.as_ref(), target, &mods, |path_mod_pak| {
+        build_bundle(&path_pak, path_mod_pak, config, &mods, rules)
+    })
+}
+
+/// Place the bundle written by `build` where `target` asks for it and install the hook if
+/// requested. The installation of `path_pak` is only touched if `target` has no output or
+/// installs the hook.
+fn install_bundle(
+    path_pak: &Path,
+    target: &IntegrationTarget,
+    mods: &[(ModInfo, PathBuf)],
+    build: impl FnOnce(&Path) -> Result<ConflictReport, IntegrationError>,
+) -> Result<(), IntegrationError> {
+    let installation = || {
+        DRGInstallation::from_pak_path(path_pak).map_err(|_| {
+            IntegrationError::DrgInstallationNotFound {
+                path: path_pak.to_path_buf(),
+            }
+        })
     };
-    let path_mod_pak = installation.paks_path().join("mods_P.pak");
 
+    let path_mod_pak = match &target.output {
+        Some(output) => output.clone(),
+        None => installation()?.paks_path().join("mods_P.pak"),
+    };
+
+    // built next to the bundle and renamed over it so a failed integration leaves the previous
+    // bundle intact
+    let temp_mod_pak = temp_file_beside(&path_mod_pak)?;
+    let conflicts = build(temp_mod_pak.path())?;
+    temp_mod_pak.persist(&path_mod_pak).map_err(|e| e.error)?;
+    log_conflicts(&conflicts);
+
+    if let Some(history) = &target.history
+        && let Err(e) = history.record(&path_mod_pak, mods)
+    {
+        warn!("failed to keep a copy of the bundle: {e}");
+    }
+    if let Some(path) = &target.conflict_report {
+        fs::write(path, serde_json::to_vec_pretty(&conflicts).unwrap())?;
+    }
+
+    if target.install_hook {
+        install_hook(&installation()?)?;
+    }
+
+    info!(
+        "{} mods installed to {}",
+        mods.len(),
+        path_mod_pak.display()
+    );
+
+    Ok(())
+}
+
+/// Install the hook DLL into the game's binaries directory if it is missing or differs from the
+/// bundled one.
+pub fn install_hook(installation: &DRGInstallation) -> Result<(), IntegrationError> {
+    #[cfg(feature = "hook")]
+    {
+        let path_hook_dll = installation
+            .binaries_directory()
+            .join(installation.installation_type.hook_dll_name());
+        let hook_dll = include_bytes!(env!("CARGO_CDYLIB_FILE_HOOK_hook"));
+        if path_hook_dll
+            .metadata()
+            .map(|m| m.len() != hook_dll.len() as u64)
+            .unwrap_or(true)
+        {
+            fs::write(&path_hook_dll, hook_dll)?;
+        }
+    }
+    #[cfg(not(feature = "hook"))]
+    let _ = installation;
+    Ok(())
+}
+
+/// Build the complete mod bundle (mod files, patched game assets, integration assets, meta and
+/// asset registry) at `path_mod_pak`. Only the game pak at `path_pak` is read, the installation
+/// it belongs to is left untouched.
+///
+/// `mods` are in ascending order of precedence, files contained in more than one mod are taken
+/// from the mod picked by `rules`. Returns which mod won each contested file.
+#[tracing::instrument(skip_all)]
+pub fn build_bundle<P: AsRef<Path>, O: AsRef<Path>>(
+    path_pak: P,
+    path_mod_pak: O,
+    config: MetaConfig,
+    mods: &[(ModInfo, PathBuf)],
+    rules: &ConflictRules,
+) -> Result<ConflictReport, IntegrationError> {
     let mut fsd_pak_reader = BufReader::new(fs::File::open(path_pak.as_ref())?);
     let fsd_pak = repak::PakBuilder::new().reader(&mut fsd_pak_reader)?;
 
@@ -254,32 +455,8 @@ pub fn integrate<P: AsRef<Path>>(
         AssetRegistry::read(&mut Cursor::new(fsd_pak.get(ar_path, &mut fsd_pak_reader)?))
             .map_err(|e| IntegrationError::GenericError { msg: e.to_string() })?;
 
-    let mut other_deferred = vec![];
-    let mut deferred = |path| {
-        other_deferred.push(path);
-        path
-    };
-
-    let pcb_path = "FSD/Content/Game/BP_PlayerControllerBase";
-    let patch_paths = [
-        "FSD/Content/Game/BP_GameInstance",
-        "FSD/Content/Game/SpaceRig/BP_PlayerController_SpaceRig",
-        "FSD/Content/Game/StartMenu/Bp_StartMenu_PlayerController",
-        "FSD/Content/UI/Menu_DeepDives/ITM_DeepDives_Join",
-        "FSD/Content/UI/Menu_ServerList/_MENU_ServerList",
-        "FSD/Content/UI/Menu_ServerList/WND_JoiningModded",
-    ];
-    let escape_menu_path = deferred("FSD/Content/UI/Menu_EscapeMenu/MENU_EscapeMenu");
-    let modding_tab_path = deferred("FSD/Content/UI/Menu_EscapeMenu/Modding/MENU_Modding");
-    let server_list_entry_path = deferred("FSD/Content/UI/Menu_ServerList/ITM_ServerList_Entry");
-
-    let mut deferred_assets: HashMap<&str, RawAsset> = HashMap::from_iter(
-        [pcb_path]
-            .iter()
-            .chain(patch_paths.iter())
-            .chain(other_deferred.iter())
-            .map(|path| (*path, RawAsset::default())),
-    );
+    let mut deferred_assets: HashMap<&str, RawAsset> =
+        HashMap::from_iter(deferred_asset_paths().map(|path| (path, RawAsset::default())));
 
     // collect assets from game pak file
     for (path, asset) in &mut deferred_assets {
@@ -302,69 +479,44 @@ pub fn integrate<P: AsRef<Path>>(
                 .write(true)
                 .create(true)
                 .truncate(true)
-                .open(&path_mod_pak)?,
+                .open(path_mod_pak.as_ref())?,
         ),
         &fsd_pak.files(),
     )?;
 
-    #[cfg(feature = "hook")]
-    {
-        let path_hook_dll = installation
-            .binaries_directory()
-            .join(installation.installation_type.hook_dll_name());
-        let hook_dll = include_bytes!(env!("CARGO_CDYLIB_FILE_HOOK_hook"));
-        if path_hook_dll
-            .metadata()
-            .map(|m| m.len() != hook_dll.len() as u64)
-            .unwrap_or(true)
-        {
-            fs::write(&path_hook_dll, hook_dll)?;
-        }
-    }
-
     let mut init_spacerig_assets = HashSet::new();
     let mut init_cave_assets = HashSet::new();
 
     let mut added_paths = HashSet::new();
 
-    for (mod_info, path) in &mods {
-        let raw_mod_file = fs::File::open(path).with_context(|_| CtxtIoSnafu {
-            mod_info: mod_info.clone(),
-        })?;
-        let mut buf = get_pak_from_data(Box::new(BufReader::new(raw_mod_file))).map_err(|e| {
-            if let IntegrationError::IoError { source } = e {
-                IntegrationError::CtxtIoError {
-                    source,
-                    mod_info: mod_info.clone(),
-                }
-            } else {
-                e
+    // collect the files of every mod first so all files of each contested asset go to the mod
+    // whose copy wins
+    let mut mod_paks = Vec::with_capacity(mods.len());
+    let mut per_path_mods: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, (mod_info, path)) in mods.iter().enumerate() {
+        let (buf, pak) = open_mod_pak(mod_info, path)?;
+        let pak_files = list_mod_pak_files(mod_info, &pak)?;
+        for normalized in pak_files.keys() {
+            let indices = per_path_mods
+                .entry(normalized.as_str().to_ascii_lowercase())
+                .or_default();
+            if indices.last() != Some(&index) {
+                indices.push(index);
             }
-        })?;
-        let pak = repak::PakBuilder::new()
-            .reader(&mut buf)
-            .with_context(|_| CtxtRepakSnafu {
-                mod_info: mod_info.clone(),
-            })?;
+        }
+        mod_paks.push((buf, pak, pak_files));
+    }
 
-        let mount = PakPath::new(pak.mount_point());
+    let specs = mods.iter().map(|(info, _)| &info.spec).collect::<Vec<_>>();
+    let winners = rules.winners(&per_path_mods, &specs);
+    let conflicts = find_conflicts(&per_path_mods, &specs, rules);
 
-        let pak_files = pak
-            .files()
+    for (index, ((mod_info, _), (mut buf, pak, pak_files))) in mods.iter().zip(mod_paks).enumerate()
+    {
+        let pak_files = pak_files
             .into_iter()
-            .map(|p| -> Result<_, IntegrationError> {
-                let j = mount.join(&p);
-                Ok((
-                    j.strip_prefix("../../../")
-                        .map_err(|_| IntegrationError::ModfileInvalidPrefix {
-                            mod_info: mod_info.clone(),
-                            modfile_path: j.to_string(),
-                        })?
-                        .to_path_buf(),
-                    p,
-                ))
-            })
-            .collect::<Result<HashMap<_, _>, _>>()?;
+            .filter(|(normalized, _)| winners[&normalized.as_str().to_ascii_lowercase()] == index)
+            .collect::<HashMap<_, _>>();
 
         for (normalized, pak_path) in &pak_files {
             match normalized.extension() {
@@ -448,9 +600,9 @@ pub fn integrate<P: AsRef<Path>>(
     }
 
     {
-        let mut pcb_asset = deferred_assets[&pcb_path].parse()?;
+        let mut pcb_asset = deferred_assets[PCB_PATH].parse()?;
         hook_pcb(&mut pcb_asset);
-        bundle.write_asset(pcb_asset, pcb_path)?;
+        bundle.write_asset(pcb_asset, PCB_PATH)?;
     }
 
     let mut patch_deferred = |path_str: &str,
@@ -462,12 +614,12 @@ pub fn integrate<P: AsRef<Path>>(
     };
 
     // apply patches to base assets
-    for patch_path in patch_paths {
+    for patch_path in PATCH_PATHS {
         patch_deferred(patch_path, patch)?;
     }
-    patch_deferred(escape_menu_path, patch_modding_tab)?;
-    patch_deferred(modding_tab_path, patch_modding_tab_item)?;
-    patch_deferred(server_list_entry_path, patch_server_list_entry)?;
+    patch_deferred(ESCAPE_MENU_PATH, patch_modding_tab)?;
+    patch_deferred(MODDING_TAB_PATH, patch_modding_tab_item)?;
+    patch_deferred(SERVER_LIST_ENTRY_PATH, patch_server_list_entry)?;
 
     let mut int_files = HashMap::new();
     collect_dir_files(&INTEGRATION_DIR, &mut int_files);
@@ -476,7 +628,7 @@ pub fn integrate<P: AsRef<Path>>(
         bundle.write_file(data, path)?;
     }
 
-    bundle.write_meta(config, &mods)?;
+    bundle.write_meta(config, mods)?;
 
     let mut buf = vec![];
     asset_registry
@@ -486,13 +638,56 @@ pub fn integrate<P: AsRef<Path>>(
 
     bundle.finish()?;
 
-    info!(
-        "{} mods installed to {}",
-        mods.len(),
-        path_mod_pak.display()
-    );
+    Ok(conflicts)
+}
 
-    Ok(())
+fn open_mod_pak(
+    mod_info: &ModInfo,
+    path: &Path,
+) -> Result<(Box<dyn ReadSeek>, repak::PakReader), IntegrationError> {
+    let raw_mod_file = fs::File::open(path).with_context(|_| CtxtIoSnafu {
+        mod_info: mod_info.clone(),
+    })?;
+    let mut buf = get_pak_from_data(Box::new(BufReader::new(raw_mod_file))).map_err(|e| {
+        if let IntegrationError::IoError { source } = e {
+            IntegrationError::CtxtIoError {
+                source,
+                mod_info: mod_info.clone(),
+            }
+        } else {
+            e
+        }
+    })?;
+    let pak = repak::PakBuilder::new()
+        .reader(&mut buf)
+        .with_context(|_| CtxtRepakSnafu {
+            mod_info: mod_info.clone(),
+        })?;
+    Ok((buf, pak))
+}
+
+/// Files of a mod pak keyed by their path relative to the pak root.
+fn list_mod_pak_files(
+    mod_info: &ModInfo,
+    pak: &repak::PakReader,
+) -> Result<HashMap<PakPathBuf, String>, IntegrationError> {
+    let mount = PakPath::new(pak.mount_point());
+
+    pak.files()
+        .into_iter()
+        .map(|p| -> Result<_, IntegrationError> {
+            let j = mount.join(&p);
+            Ok((
+                j.strip_prefix("../../../")
+                    .map_err(|_| IntegrationError::ModfileInvalidPrefix {
+                        mod_info: mod_info.clone(),
+                        modfile_path: j.to_string(),
+                    })?
+                    .to_path_buf(),
+                p,
+            ))
+        })
+        .collect()
 }
 
 fn collect_dir_files(dir: &'static include_dir::Dir, collect: &mut HashMap<String, &[u8]>) {
@@ -525,17 +720,18 @@ fn format_soft_class<P: AsRef<PakPath>>(path: P) -> String {
     )
 }
 
-struct ModBundleWriter<W: Write + Seek> {
-    pak_writer: PakWriter<W>,
+/// Case insensitive tree of paths, used to match the case of other paths to them.
+#[derive(Debug, Default)]
+pub(crate) struct PathCaseNormalizer {
     directories: HashMap<String, Dir>,
 }
 
-impl<W: Write + Seek> ModBundleWriter<W> {
-    fn new(writer: W, fsd_paths: &[String]) -> Result<Self, IntegrationError> {
+impl PathCaseNormalizer {
+    pub(crate) fn new<S: AsRef<str>>(paths: impl IntoIterator<Item = S>) -> Self {
         let mut directories: HashMap<String, Dir> = HashMap::new();
-        for f in fsd_paths {
+        for f in paths {
             let mut dir = &mut directories;
-            for c in PakPath::new(f).components() {
+            for c in PakPath::new(f.as_ref()).components() {
                 dir = &mut dir
                     .entry(c.as_str().to_ascii_lowercase())
                     .or_insert(Dir {
@@ -545,16 +741,12 @@ impl<W: Write + Seek> ModBundleWriter<W> {
                     .children;
             }
         }
-
-        Ok(Self {
-            pak_writer: repak::PakBuilder::new()
-                .compression([repak::Compression::Zlib])
-                .writer(writer, repak::Version::V11, "../../../".to_string(), None),
-            directories,
-        })
+        Self { directories }
     }
-    /// Used to normalize match path case to existing files in the DRG pak.
-    fn normalize_path(&self, path_str: &str) -> PakPathBuf {
+
+    /// Match the case of each component of `path_str` to the first known path containing it.
+    /// Unknown components are kept as they are.
+    pub(crate) fn normalize(&self, path_str: &str) -> PakPathBuf {
         let mut dir = Some(&self.directories);
         let path = PakPath::new(path_str);
         let mut normalized_path = PakPathBuf::new();
@@ -568,10 +760,27 @@ impl<W: Write + Seek> ModBundleWriter<W> {
         }
         normalized_path
     }
+}
+
+struct ModBundleWriter<W: Write + Seek> {
+    pak_writer: PakWriter<W>,
+    /// Used to normalize match path case to existing files in the DRG pak.
+    fsd_paths: PathCaseNormalizer,
+}
+
+impl<W: Write + Seek> ModBundleWriter<W> {
+    fn new(writer: W, fsd_paths: &[String]) -> Result<Self, IntegrationError> {
+        Ok(Self {
+            pak_writer: repak::PakBuilder::new()
+                .compression([repak::Compression::Zlib])
+                .writer(writer, repak::Version::V11, "../../../".to_string(), None),
+            fsd_paths: PathCaseNormalizer::new(fsd_paths),
+        })
+    }
 
     fn write_file(&mut self, data: &[u8], path: &str) -> Result<(), IntegrationError> {
         self.pak_writer
-            .write_file(self.normalize_path(path).as_str(), data)?;
+            .write_file(self.fsd_paths.normalize(path).as_str(), data)?;
         Ok(())
     }
 
@@ -611,8 +820,8 @@ impl<W: Write + Seek> ModBundleWriter<W> {
                 .iter()
                 .map(|(info, _)| MetaMod {
                     name: info.name.clone(),
-                    version: "TODO".into(), // TODO
-                    author: "TODO".into(),  // TODO
+                    version: info.version.clone().unwrap_or_default(),
+                    author: info.author.clone().unwrap_or_default(),
                     required: info.suggested_require,
                     url: info.resolution.get_resolvable_url_or_name().to_string(),
                     approval: info
@@ -622,8 +831,9 @@ impl<W: Write + Seek> ModBundleWriter<W> {
                         .unwrap_or(ApprovalStatus::Sandbox),
                 })
                 .collect(),
+            format_version: META_FORMAT_VERSION,
         };
-        self.write_file(&postcard::to_allocvec(&meta).unwrap(), "meta")?;
+        self.write_file(&meta.to_bytes(), "meta")?;
         Ok(())
     }
 
@@ -1265,3 +1475,76 @@ fn patch_server_list_entry<C: Seek + Read>(asset: &mut Asset<C>) -> Result<(), I
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Every file below `dir` with its contents.
+    fn snapshot(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(snapshot(&path));
+            } else {
+                files.insert(path.clone(), fs::read(&path).unwrap());
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn test_install_bundle_to_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let game = dir.path().join("FSD");
+        let paks = game.join("Content/Paks");
+        fs::create_dir_all(&paks).unwrap();
+        fs::create_dir_all(game.join("Binaries/Win64")).unwrap();
+        let path_pak = paks.join("FSD-WindowsNoEditor.pak");
+        fs::write(&path_pak, "game").unwrap();
+        fs::write(paks.join("mods_P.pak"), "installed").unwrap();
+        let before = snapshot(dir.path());
+
+        let out_dir = dir.path().join("out");
+        fs::create_dir(&out_dir).unwrap();
+        let output = out_dir.join("bundle.pak");
+        let target = IntegrationTarget {
+            output: Some(output.clone()),
+            install_hook: false,
+            ..Default::default()
+        };
+        install_bundle(&path_pak, &target, &[], |path| {
+            fs::write(path, "bundle")?;
+            Ok(ConflictReport::default())
+        })
+        .unwrap();
+
+        assert_eq!(fs::read(&output).unwrap(), b"bundle");
+        // only the bundle is written to the output directory, the game is left untouched
+        let mut after = snapshot(dir.path());
+        after.remove(&output);
+        assert_eq!(after, before);
+
+        // a failed build leaves the previous bundle in place
+        let result = install_bundle(&path_pak, &target, &[], |path| {
+            fs::write(path, "partial")?;
+            GenericSnafu { msg: "failed" }.fail()
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read(&output).unwrap(), b"bundle");
+        assert_eq!(fs::read_dir(&out_dir).unwrap().count(), 1);
+
+        // without an output the bundle replaces mods_P.pak in the game's paks directory
+        let target = IntegrationTarget {
+            install_hook: false,
+            ..Default::default()
+        };
+        install_bundle(&path_pak, &target, &[], |path| {
+            fs::write(path, "bundle")?;
+            Ok(ConflictReport::default())
+        })
+        .unwrap();
+        assert_eq!(fs::read(paks.join("mods_P.pak")).unwrap(), b"bundle");
+    }
+}
@@ -1,22 +1,28 @@
 #![feature(let_chains)]
 #![feature(if_let_guard)]
 
+pub mod conflicts;
+pub mod dependencies;
 pub mod gui;
+pub mod history;
 pub mod integrate;
+pub mod lockfile;
 pub mod mod_lints;
 pub mod providers;
 pub mod state;
+pub mod watch;
 
+use std::collections::HashSet;
 use std::ops::Deref;
-use std::{
-    collections::HashSet,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
+use conflicts::ConflictRules;
+use dependencies::{resolve_dependencies, MissingDependencyPolicy};
 use directories::ProjectDirs;
 use fs_err as fs;
-use integrate::IntegrationError;
-use providers::{ModResolution, ModSpecification, ProviderError, ProviderFactory};
+use integrate::{IntegrationError, IntegrationTarget};
+use lockfile::{HashFailedSnafu, LockedMod, Lockfile};
+use providers::{BlobGcStats, ModResolution, ModSpecification, ProviderError, ProviderFactory};
 use snafu::prelude::*;
 use state::{State, StateError};
 use tracing::*;
@@ -101,39 +107,125 @@ pub fn is_drg_pak<P: AsRef<Path>>(path: P) -> Result<(), MintError> {
     Ok(())
 }
 
+/// Temporary file in the directory of `path`, to be persisted over it once completely written so
+/// a crash can't leave a half-written file behind.
+pub(crate) fn temp_file_beside(path: &Path) -> std::io::Result<tempfile::NamedTempFile> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    tempfile::NamedTempFile::new_in(parent)
+}
+
 pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
     game_path: P,
     state: &State,
     mod_specs: &[ModSpecification],
     update: bool,
+    policy: MissingDependencyPolicy,
+    target: &IntegrationTarget,
+    rules: &ConflictRules,
 ) -> Result<(), IntegrationError> {
     let mods = state.store.resolve_mods(mod_specs, update).await?;
 
-    let mods_set = mod_specs
+    let resolved = resolve_dependencies(mod_specs, &mods, policy)?;
+    resolved.log_added();
+
+    let to_integrate = resolved
+        .mods
+        .into_iter()
+        .map(|(_, info)| info)
+        .collect::<Vec<_>>();
+    let urls = to_integrate
         .iter()
-        .flat_map(|m| [&mods[m].spec.url, &mods[m].resolution.url.0])
-        .collect::<HashSet<_>>();
+        .map(|m| &m.resolution)
+        .collect::<Vec<_>>();
+
+    info!("fetching mods...");
+    let paths = state.store.fetch_mods_ordered(&urls, update, None).await?;
+
+    integrate::integrate_to(
+        game_path,
+        target,
+        state.config.deref().into(),
+        to_integrate.into_iter().zip(paths).collect(),
+        &rules.map_specs(|s| state.store.canonical_spec(s)),
+    )
+}
 
-    // TODO need more rebust way of detecting whether dependencies are missing
-    let missing_deps = mod_specs
+/// Resolve and fetch a profile's mods including their dependencies and record exactly what was
+/// fetched.
+pub async fn resolve_and_lock(
+    state: &State,
+    profile: &str,
+    mod_specs: &[ModSpecification],
+    update: bool,
+    policy: MissingDependencyPolicy,
+) -> Result<Lockfile, IntegrationError> {
+    let mods = state.store.resolve_mods(mod_specs, update).await?;
+
+    let resolved = resolve_dependencies(mod_specs, &mods, policy)?;
+    resolved.log_added();
+
+    let resolutions = resolved
+        .mods
         .iter()
-        .flat_map(|m| {
-            mods[m]
-                .suggested_dependencies
-                .iter()
-                .filter_map(|m| (!mods_set.contains(&m.url)).then_some(&m.url))
-        })
-        .collect::<HashSet<_>>();
-    if !missing_deps.is_empty() {
-        warn!("the following dependencies are missing:");
-        for d in missing_deps {
-            warn!("  {d}");
-        }
+        .map(|(_, info)| &info.resolution)
+        .collect::<Vec<_>>();
+
+    info!("fetching mods...");
+    let paths = state
+        .store
+        .fetch_mods_ordered(&resolutions, update, None)
+        .await?;
+
+    let mut locked = vec![];
+    for ((spec, info), path) in resolved.mods.iter().zip(paths) {
+        locked.push(LockedMod {
+            spec: spec.clone(),
+            resolution: info.resolution.url.clone(),
+            modio_file_id: providers::modio::parse_modfile_id(&info.resolution.url.0),
+            sha256: providers::sha256_file(&path).context(HashFailedSnafu { path: &path })?,
+            required_by: resolved.added.get(spec).cloned().unwrap_or_default(),
+        });
+    }
+
+    Ok(Lockfile {
+        profile: profile.to_owned(),
+        mods: locked,
+    })
+}
+
+/// Integrate the exact mod versions recorded in `lockfile`, refusing to do so if any fetched mod
+/// file differs from the one that was locked.
+pub async fn resolve_locked_and_integrate<P: AsRef<Path>>(
+    game_path: P,
+    state: &State,
+    lockfile: &Lockfile,
+    mod_specs: &[ModSpecification],
+    target: &IntegrationTarget,
+    rules: &ConflictRules,
+) -> Result<(), IntegrationError> {
+    // dependencies that were added when locking come first so the mods requiring them take
+    // precedence
+    let mut locked = lockfile
+        .mods
+        .iter()
+        .filter(|m| !m.required_by.is_empty())
+        .collect::<Vec<_>>();
+    for spec in mod_specs {
+        locked.push(lockfile.get(spec)?);
     }
+    let pinned_specs = locked
+        .iter()
+        .map(|l| ModSpecification::new(l.resolution.0.clone()))
+        .collect::<Vec<_>>();
+
+    let mods = state.store.resolve_mods(&pinned_specs, false).await?;
 
-    let to_integrate = mod_specs
+    let to_integrate = pinned_specs
         .iter()
-        .map(|u| mods[u].clone())
+        .map(|s| mods[s].clone())
         .collect::<Vec<_>>();
     let urls = to_integrate
         .iter()
@@ -141,12 +233,18 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
         .collect::<Vec<_>>();
 
     info!("fetching mods...");
-    let paths = state.store.fetch_mods(&urls, update, None).await?;
+    let paths = state.store.fetch_mods_ordered(&urls, false, None).await?;
+
+    for (locked, path) in locked.iter().zip(&paths) {
+        locked.verify(path)?;
+    }
 
-    integrate::integrate(
+    integrate::integrate_to(
         game_path,
+        target,
         state.config.deref().into(),
         to_integrate.into_iter().zip(paths).collect(),
+        &rules.map_specs(|s| state.store.canonical_spec(s)),
     )
 }
 
@@ -156,27 +254,8 @@ async fn resolve_into_urls(
 ) -> Result<Vec<ModResolution>, MintError> {
     let mods = state.store.resolve_mods(mod_specs, false).await?;
 
-    let mods_set = mod_specs
-        .iter()
-        .flat_map(|m| [&mods[m].spec.url, &mods[m].resolution.url.0])
-        .collect::<HashSet<_>>();
-
-    // TODO need more rebust way of detecting whether dependencies are missing
-    let missing_deps = mod_specs
-        .iter()
-        .flat_map(|m| {
-            mods[m]
-                .suggested_dependencies
-                .iter()
-                .filter_map(|m| (!mods_set.contains(&m.url)).then_some(&m.url))
-        })
-        .collect::<HashSet<_>>();
-    if !missing_deps.is_empty() {
-        warn!("the following dependencies are missing:");
-        for d in missing_deps {
-            warn!("  {d}");
-        }
-    }
+    resolve_dependencies(mod_specs, &mods, MissingDependencyPolicy::Warn)
+        .map_err(IntegrationError::from)?;
 
     let urls = mod_specs
         .iter()
@@ -194,15 +273,19 @@ pub async fn resolve_ordered(
     let urls = resolve_into_urls(state, mod_specs).await?;
     Ok(state
         .store
-        .fetch_mods(&urls.iter().collect::<Vec<_>>(), false, None)
+        .fetch_mods_ordered(&urls.iter().collect::<Vec<_>>(), false, None)
         .await?)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn resolve_unordered_and_integrate_with_provider_init<P, F>(
     game_path: P,
     state: &mut State,
     mod_specs: &[ModSpecification],
     update: bool,
+    policy: MissingDependencyPolicy,
+    target: &IntegrationTarget,
+    rules: &ConflictRules,
     init: F,
 ) -> Result<(), MintError>
 where
@@ -210,7 +293,65 @@ where
     F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
 {
     loop {
-        match resolve_unordered_and_integrate(&game_path, state, mod_specs, update).await {
+        match resolve_unordered_and_integrate(
+            &game_path, state, mod_specs, update, policy, target, rules,
+        )
+        .await
+        {
+            Ok(()) => return Ok(()),
+            Err(ref e)
+                if let IntegrationError::ProviderError { ref source } = e
+                    && let ProviderError::NoProvider { ref url, factory } = source =>
+            {
+                init(state, url.clone(), factory)?
+            }
+            Err(e) => Err(e)?,
+        }
+    }
+}
+
+pub async fn resolve_and_lock_with_provider_init<F>(
+    state: &mut State,
+    profile: &str,
+    mod_specs: &[ModSpecification],
+    update: bool,
+    policy: MissingDependencyPolicy,
+    init: F,
+) -> Result<Lockfile, MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match resolve_and_lock(state, profile, mod_specs, update, policy).await {
+            Ok(lockfile) => return Ok(lockfile),
+            Err(ref e)
+                if let IntegrationError::ProviderError { ref source } = e
+                    && let ProviderError::NoProvider { ref url, factory } = source =>
+            {
+                init(state, url.clone(), factory)?
+            }
+            Err(e) => Err(e)?,
+        }
+    }
+}
+
+pub async fn resolve_locked_and_integrate_with_provider_init<P, F>(
+    game_path: P,
+    state: &mut State,
+    lockfile: &Lockfile,
+    mod_specs: &[ModSpecification],
+    target: &IntegrationTarget,
+    rules: &ConflictRules,
+    init: F,
+) -> Result<(), MintError>
+where
+    P: AsRef<Path>,
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match resolve_locked_and_integrate(&game_path, state, lockfile, mod_specs, target, rules)
+            .await
+        {
             Ok(()) => return Ok(()),
             Err(ref e)
                 if let IntegrationError::ProviderError { ref source } = e
@@ -246,3 +387,48 @@ where
         }
     }
 }
+
+/// Delete cached mod files not used by any profile or lockfile, keeping the `keep` newest cached
+/// versions of every mod in a profile.
+pub fn gc_cache(state: &State, keep: usize, dry_run: bool) -> Result<BlobGcStats, MintError> {
+    let mut specs = vec![];
+    let mut locked_hashes = HashSet::new();
+    for profile in state.mod_data.profiles.keys() {
+        state
+            .mod_data
+            .for_each_mod(profile, |mc| specs.push(mc.spec.clone()));
+        let lockfile_path = Lockfile::default_path(&state.dirs.config_dir, profile);
+        if lockfile_path.exists() {
+            match Lockfile::read(&lockfile_path) {
+                Ok(lockfile) => locked_hashes.extend(lockfile.mods.into_iter().map(|m| m.sha256)),
+                Err(e) => warn!("ignoring unreadable lockfile: {e}"),
+            }
+        }
+    }
+    Ok(state
+        .store
+        .gc_blobs(&specs, &locked_hashes, keep, dry_run)?)
+}
+
+pub fn gc_cache_with_provider_init<F>(
+    state: &mut State,
+    keep: usize,
+    dry_run: bool,
+    init: F,
+) -> Result<BlobGcStats, MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match gc_cache(state, keep, dry_run) {
+            Ok(stats) => return Ok(stats),
+            Err(ref e)
+                if let MintError::ProviderError { ref source } = e
+                    && let ProviderError::NoProvider { ref url, factory } = source =>
+            {
+                init(state, url.clone(), factory)?
+            }
+            Err(e) => Err(e)?,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::providers::{ModIdentifier, ModSpecification};
+
+/// A profile pinned to the exact mod versions and file contents it resolved to when it was
+/// locked, so the same bundle can be reproduced later or on another machine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub profile: String,
+    pub mods: Vec<LockedMod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedMod {
+    /// Specification as it appears in the profile.
+    pub spec: ModSpecification,
+    /// Exact version the specification resolved to.
+    pub resolution: ModIdentifier,
+    /// Only available for mods from mod.io.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modio_file_id: Option<u32>,
+    /// sha256 of the fetched mod file, identical to its `BlobRef` for cached blobs.
+    pub sha256: String,
+    /// Set for dependencies that were not part of the profile, the mods that pulled them in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_by: Vec<ModSpecification>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub enum VersionAnnotatedLockfile {
+    #[serde(rename = "0.0.0")]
+    V0_0_0(Lockfile),
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum LockfileError {
+    #[snafu(display("failed to read lockfile {}", path.display()))]
+    ReadFailed {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("failed to parse lockfile {}", path.display()))]
+    DeserializationFailed {
+        source: serde_json::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("failed to write lockfile {}", path.display()))]
+    WriteFailed {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(transparent)]
+    PersistError { source: tempfile::PersistError },
+    #[snafu(display("failed to hash mod file {}", path.display()))]
+    HashFailed {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("mod {} is not in the lockfile, re-run `mint lock`", spec.url))]
+    NotLocked { spec: ModSpecification },
+    #[snafu(display(
+        "mod {} does not match the lockfile: expected sha256 {expected}, found {found}",
+        spec.url
+    ))]
+    HashMismatch {
+        spec: ModSpecification,
+        expected: String,
+        found: String,
+    },
+}
+
+impl Lockfile {
+    /// Default lockfile location for a profile.
+    pub fn default_path<P: AsRef<Path>>(config_dir: P, profile: &str) -> PathBuf {
+        let name = profile
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        config_dir
+            .as_ref()
+            .join("locks")
+            .join(format!("{name}.lock.json"))
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, LockfileError> {
+        let path = path.as_ref();
+        let buf = fs::read(path).context(ReadFailedSnafu { path })?;
+        let VersionAnnotatedLockfile::V0_0_0(lockfile) =
+            serde_json::from_slice(&buf).context(DeserializationFailedSnafu { path })?;
+        Ok(lockfile)
+    }
+
+    /// Written to a temporary file first so an interrupted write can't leave a broken lockfile.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), LockfileError> {
+        let path = path.as_ref();
+        let parent = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(parent).context(WriteFailedSnafu { path })?;
+        let mut temp_file =
+            tempfile::NamedTempFile::new_in(parent).context(WriteFailedSnafu { path })?;
+        temp_file
+            .write_all(
+                &serde_json::to_vec_pretty(&VersionAnnotatedLockfile::V0_0_0(self.clone()))
+                    .unwrap(),
+            )
+            .context(WriteFailedSnafu { path })?;
+        temp_file.persist(path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, spec: &ModSpecification) -> Result<&LockedMod, LockfileError> {
+        self.mods
+            .iter()
+            .find(|m| &m.spec == spec)
+            .context(NotLockedSnafu { spec: spec.clone() })
+    }
+}
+
+impl LockedMod {
+    /// Check that a fetched mod file is the one that was locked.
+    pub fn verify<P: AsRef<Path>>(&self, path: P) -> Result<(), LockfileError> {
+        let path = path.as_ref();
+        let found = crate::providers::sha256_file(path).context(HashFailedSnafu { path })?;
+        ensure!(
+            found == self.sha256,
+            HashMismatchSnafu {
+                spec: self.spec.clone(),
+                expected: self.sha256.clone(),
+                found,
+            }
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mod.pak");
+        fs::write(&path, b"mod").unwrap();
+
+        let mut locked = LockedMod {
+            spec: ModSpecification::new("https://mod.io/g/drg/m/foo".into()),
+            resolution: "https://mod.io/g/drg/m/foo#1/2".into(),
+            modio_file_id: Some(2),
+            sha256: crate::providers::sha256_file(&path).unwrap(),
+            required_by: vec![],
+        };
+        assert!(locked.verify(&path).is_ok());
+
+        locked.sha256 = "0".repeat(64);
+        assert!(matches!(
+            locked.verify(&path),
+            Err(LockfileError::HashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Lockfile::default_path(dir.path(), "a/b");
+        assert_eq!(path.file_name().unwrap(), "a_b.lock.json");
+
+        let lockfile = Lockfile {
+            profile: "a/b".into(),
+            mods: vec![LockedMod {
+                spec: ModSpecification::new("https://example.org/mod.pak".into()),
+                resolution: "https://example.org/mod.pak".into(),
+                modio_file_id: None,
+                sha256: "0".repeat(64),
+                required_by: vec![],
+            }],
+        };
+        lockfile.write(&path).unwrap();
+        let read = Lockfile::read(&path).unwrap();
+        assert_eq!(read.profile, lockfile.profile);
+        assert_eq!(read.mods[0].spec, lockfile.mods[0].spec);
+    }
+}
@@ -1,17 +1,29 @@
-use std::collections::BTreeSet;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::{Parser, Subcommand};
 use tracing::{debug, info};
 
-use mint::mod_lints::{run_lints, LintId};
+use mint::conflicts::ConflictRules;
+use mint::dependencies::MissingDependencyPolicy;
+use mint::integrate::{restore_uninstall_backup, uninstall, IntegrationTarget};
+use mint::lockfile::Lockfile;
+use mint::mod_lints::{
+    run_lints_with_conflict_rules, LintFactory, LintFormat, LintId, LintSuppression, Severity,
+};
 use mint::providers::ProviderFactory;
-use mint::{gui::gui, providers::ModSpecification, state::State};
+use mint::state::share::{ImportMode, SharedProfile};
+use mint::state::{ModConfig, ModData_v0_1_0 as ModData, ModOrGroup};
+use mint::watch::{ModWatcher, POLL_INTERVAL};
 use mint::{
-    resolve_ordered_with_provider_init, resolve_unordered_and_integrate_with_provider_init, Dirs,
-    MintError,
+    gc_cache_with_provider_init, resolve_and_lock_with_provider_init,
+    resolve_locked_and_integrate_with_provider_init, resolve_ordered_with_provider_init,
+    resolve_unordered_and_integrate_with_provider_init, Dirs, MintError,
 };
+use mint::{gui::gui, providers::ModSpecification, state::State};
+use mint_lib::DRGInstallation;
 
 /// Command line integration tool.
 #[derive(Parser, Debug)]
@@ -27,7 +39,30 @@ struct ActionIntegrate {
     #[arg(short, long)]
     update: bool,
 
-    /// Paths of mods to integrate
+    /// Only use cached mods and metadata, never access the network. Mods that are not cached
+    /// fail to resolve. Defaults to the configured behavior.
+    #[arg(long, conflicts_with = "update")]
+    offline: bool,
+
+    /// Write the mod bundle to this path instead of the game's Paks directory. Together with
+    /// --no-hook the game installation is not modified at all.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Do not install the hook DLL into the game's Binaries directory.
+    #[arg(long)]
+    no_hook: bool,
+
+    /// What to do with dependencies that are not part of the mods to integrate. Defaults to the
+    /// configured behavior.
+    #[arg(long, value_enum)]
+    missing_deps: Option<MissingDependencyPolicy>,
+
+    /// Also write the report of files contained in more than one mod to this path as JSON.
+    #[arg(long)]
+    conflict_report: Option<PathBuf>,
+
+    /// Paths of mods to integrate. If several mods contain the same file, the one listed last wins.
     ///
     /// Can be a file path or URL to a .pak or .zip file or a URL to a mod on https://mod.io/g/drg
     /// Examples:
@@ -52,10 +87,330 @@ struct ActionIntegrateProfile {
     #[arg(short, long)]
     update: bool,
 
+    /// Only use cached mods and metadata, never access the network. Mods that are not cached
+    /// fail to resolve. Defaults to the configured behavior.
+    #[arg(long, conflicts_with = "update")]
+    offline: bool,
+
+    /// Write the mod bundle to this path instead of the game's Paks directory. Together with
+    /// --no-hook the game installation is not modified at all.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Do not install the hook DLL into the game's Binaries directory.
+    #[arg(long)]
+    no_hook: bool,
+
+    /// What to do with dependencies that are not part of the mods to integrate. Defaults to the
+    /// configured behavior.
+    #[arg(long, value_enum)]
+    missing_deps: Option<MissingDependencyPolicy>,
+
+    /// Also write the report of files contained in more than one mod to this path as JSON.
+    #[arg(long)]
+    conflict_report: Option<PathBuf>,
+
+    /// Integrate the exact mod versions recorded by `mint lock` and refuse to integrate if any
+    /// fetched mod does not match the lockfile.
+    #[arg(long, conflicts_with = "update")]
+    locked: bool,
+
+    /// Lockfile to use with --locked. Defaults to the profile's lockfile in the config directory.
+    #[arg(long, requires = "locked")]
+    lockfile: Option<PathBuf>,
+
     /// Profile to integrate.
     profile: String,
 }
 
+/// Export a profile together with the mod groups it uses so it can be imported elsewhere
+#[derive(Parser, Debug)]
+struct ActionProfileExport {
+    /// Print a compact share code instead of JSON.
+    #[arg(long)]
+    code: bool,
+
+    /// Write to this file instead of standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Profile to export.
+    profile: String,
+}
+
+/// Import a profile exported with `mint profile export` or copied from the GUI
+#[derive(Parser, Debug)]
+struct ActionProfileImport {
+    /// What to do if a profile or mod group of the same name already exists.
+    #[arg(long, value_enum, default_value_t)]
+    on_conflict: ImportMode,
+
+    /// Path of an exported profile, `-` to read it from standard input, or a share code.
+    input: String,
+}
+
+#[derive(Parser, Debug)]
+struct ActionName {
+    name: String,
+}
+
+#[derive(Parser, Debug)]
+struct ActionRename {
+    name: String,
+    new_name: String,
+}
+
+/// Add mods or mod groups to a profile
+#[derive(Parser, Debug)]
+struct ActionProfileAdd {
+    /// Add mod groups by name instead of mods by URL.
+    #[arg(long)]
+    group: bool,
+
+    /// Mark the added mods as required. Has no effect on groups.
+    #[arg(long, conflicts_with = "group")]
+    required: bool,
+
+    /// Add the mods or groups disabled.
+    #[arg(long)]
+    disabled: bool,
+
+    /// Profile to add to.
+    profile: String,
+
+    /// URLs of the mods, or names of the groups with --group.
+    #[arg(required = true)]
+    entries: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ActionProfileEntries {
+    /// Select mod groups by name instead of mods by URL.
+    #[arg(long)]
+    group: bool,
+
+    profile: String,
+
+    /// URLs of the mods, or names of the groups with --group.
+    #[arg(required = true)]
+    entries: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileAction {
+    Run(ActionIntegrateProfile),
+    Export(ActionProfileExport),
+    Import(ActionProfileImport),
+    /// List profiles, marking the active one
+    List,
+    /// Create an empty profile
+    Create(ActionName),
+    /// Rename a profile
+    Rename(ActionRename),
+    /// Delete a profile
+    Delete(ActionName),
+    /// Set the profile selected in the GUI
+    SetActive(ActionName),
+    Add(ActionProfileAdd),
+    /// Remove mods or mod groups from a profile
+    Remove(ActionProfileEntries),
+    /// Enable mods or mod groups of a profile
+    Enable(ActionProfileEntries),
+    /// Disable mods or mod groups of a profile
+    Disable(ActionProfileEntries),
+    /// Mark mods of a profile as required
+    Require(ActionProfileEntries),
+    /// Mark mods of a profile as optional
+    Unrequire(ActionProfileEntries),
+}
+
+/// Delete a mod group
+#[derive(Parser, Debug)]
+struct ActionGroupDelete {
+    /// Also remove the group from the profiles that contain it.
+    #[arg(long)]
+    force: bool,
+
+    name: String,
+}
+
+/// Add mods to a mod group
+#[derive(Parser, Debug)]
+struct ActionGroupAdd {
+    /// Mark the added mods as required.
+    #[arg(long)]
+    required: bool,
+
+    /// Add the mods disabled.
+    #[arg(long)]
+    disabled: bool,
+
+    /// Group to add to.
+    group: String,
+
+    /// URLs of the mods.
+    #[arg(required = true)]
+    mods: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ActionGroupMods {
+    group: String,
+
+    /// URLs of the mods.
+    #[arg(required = true)]
+    mods: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum GroupAction {
+    /// List mod groups with their mods
+    List,
+    /// Create an empty mod group
+    Create(ActionName),
+    /// Rename a mod group, updating the profiles that contain it
+    Rename(ActionRename),
+    Delete(ActionGroupDelete),
+    Add(ActionGroupAdd),
+    /// Remove mods from a mod group
+    Remove(ActionGroupMods),
+    /// Enable mods of a mod group
+    Enable(ActionGroupMods),
+    /// Disable mods of a mod group
+    Disable(ActionGroupMods),
+    /// Mark mods of a mod group as required
+    Require(ActionGroupMods),
+    /// Mark mods of a mod group as optional
+    Unrequire(ActionGroupMods),
+}
+
+/// Integrate a profile with `run`, or manage profiles with the other subcommands
+#[derive(Parser, Debug)]
+struct ActionProfile {
+    #[command(subcommand)]
+    action: ProfileAction,
+}
+
+/// Manage mod groups, sets of mods that can be added to profiles as a whole
+#[derive(Parser, Debug)]
+struct ActionGroup {
+    #[command(subcommand)]
+    action: GroupAction,
+}
+
+/// Integrate a profile and integrate it again whenever one of its local mod files or directories
+/// changes
+#[derive(Parser, Debug)]
+struct ActionWatch {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Write the mod bundle to this path instead of the game's Paks directory. Together with
+    /// --no-hook the game installation is not modified at all.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Only use cached mods and metadata, never access the network. Mods that are not cached
+    /// fail to resolve. Defaults to the configured behavior.
+    #[arg(long)]
+    offline: bool,
+
+    /// Do not install the hook DLL into the game's Binaries directory.
+    #[arg(long)]
+    no_hook: bool,
+
+    /// What to do with dependencies that are not part of the mods to integrate. Defaults to the
+    /// configured behavior.
+    #[arg(long, value_enum)]
+    missing_deps: Option<MissingDependencyPolicy>,
+
+    /// Milliseconds local mods must stop changing for before integrating again.
+    #[arg(long, value_name = "MS", default_value_t = 1000)]
+    debounce: u64,
+
+    /// Profile to watch.
+    profile: String,
+}
+
+/// Remove the mod bundle and hook DLL and re-enable the official mod.io integration
+#[derive(Parser, Debug)]
+struct ActionUninstall {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Enable the mod.io mods of this profile in the official integration. All other installed
+    /// mod.io mods are disabled.
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Enable the mod.io mod with this ID in the official integration. Can be repeated.
+    #[arg(long, value_name = "ID")]
+    enable: Vec<u32>,
+
+    /// Restore the GameUserSettings.ini backed up by the last uninstall instead of uninstalling.
+    #[arg(long, conflicts_with_all = ["profile", "enable"])]
+    restore_backup: bool,
+}
+
+/// Restore a previously integrated mod bundle
+#[derive(Parser, Debug)]
+struct ActionRollback {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// List the kept bundles instead of restoring one.
+    #[arg(long)]
+    list: bool,
+
+    /// Number of integrations to go back, 0 being the latest.
+    #[arg(default_value_t = 1, conflicts_with = "list")]
+    n: usize,
+}
+
+/// Write a lockfile recording the exact resolved version and file hash of every mod in a profile
+#[derive(Parser, Debug)]
+struct ActionLock {
+    /// Update mods before locking them.
+    #[arg(short, long)]
+    update: bool,
+
+    /// Only use cached mods and metadata, never access the network. Mods that are not cached
+    /// fail to resolve. Defaults to the configured behavior.
+    #[arg(long, conflicts_with = "update")]
+    offline: bool,
+
+    /// What to do with dependencies that are not part of the mods to integrate. Defaults to the
+    /// configured behavior.
+    #[arg(long, value_enum)]
+    missing_deps: Option<MissingDependencyPolicy>,
+
+    /// Where to write the lockfile. Defaults to the profile's lockfile in the config directory.
+    #[arg(long)]
+    lockfile: Option<PathBuf>,
+
+    /// Profile to lock.
+    profile: String,
+}
+
+fn parse_suppression(s: &str) -> Result<LintSuppression, String> {
+    let (lint, spec) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected LINT=MOD, found `{s}`"))?;
+    Ok(LintSuppression::new(
+        lint.parse()?,
+        ModSpecification::new(spec.to_string()),
+    ))
+}
+
 /// Launch via steam
 #[derive(Parser, Debug)]
 struct ActionLaunch {
@@ -71,16 +426,90 @@ struct ActionLint {
     #[arg(short, long)]
     fsd_pak: Option<PathBuf>,
 
+    /// Only use cached mods and metadata, never access the network. Mods that are not cached
+    /// fail to resolve. Defaults to the configured behavior.
+    #[arg(long)]
+    offline: bool,
+
+    /// Run all lints, including ones that are disabled by default.
+    #[arg(long)]
+    all: bool,
+
+    /// Run this lint in addition to the default ones. Can be repeated.
+    #[arg(long, value_name = "LINT")]
+    enable: Vec<LintId>,
+
+    /// Do not run this lint. Can be repeated.
+    #[arg(long, value_name = "LINT")]
+    disable: Vec<LintId>,
+
+    /// Add a suppression to the profile, hiding the findings of LINT for MOD in this and future
+    /// runs. Can be repeated.
+    #[arg(long, value_name = "LINT=MOD", value_parser = parse_suppression)]
+    suppress: Vec<LintSuppression>,
+
+    /// Also show findings hidden by the profile's suppressions.
+    #[arg(long)]
+    no_suppressions: bool,
+
+    /// Output format of the report.
+    #[arg(long, value_enum, default_value_t)]
+    format: LintFormat,
+
+    /// Exit with an error if there are findings of this severity or higher.
+    #[arg(long, value_enum)]
+    fail_on: Option<Severity>,
+
+    /// List the available lints and exit.
+    #[arg(long)]
+    list: bool,
+
     /// Profile to lint.
-    profile: String,
+    #[arg(required_unless_present = "list")]
+    profile: Option<String>,
+}
+
+/// Delete cached mod files that are not used by any profile or lockfile
+#[derive(Parser, Debug)]
+struct ActionCacheGc {
+    /// Only report what would be deleted and how much space it would reclaim.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Also keep the N newest cached versions of every mod in a profile.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep: usize,
+}
+
+/// Check cached mod files against their hashes and delete corrupt ones so they are fetched again
+#[derive(Parser, Debug)]
+struct ActionCacheVerify {}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    Gc(ActionCacheGc),
+    Verify(ActionCacheVerify),
+}
+
+/// Manage the cache of downloaded mods
+#[derive(Parser, Debug)]
+struct ActionCache {
+    #[command(subcommand)]
+    action: CacheAction,
 }
 
 #[derive(Subcommand, Debug)]
 enum Action {
     Integrate(ActionIntegrate),
-    Profile(ActionIntegrateProfile),
+    Profile(ActionProfile),
+    Group(ActionGroup),
+    Lock(ActionLock),
+    Watch(ActionWatch),
+    Uninstall(ActionUninstall),
+    Rollback(ActionRollback),
     Launch(ActionLaunch),
     Lint(ActionLint),
+    Cache(ActionCache),
 }
 
 #[derive(Parser, Debug)]
@@ -130,10 +559,26 @@ fn main() -> Result<()> {
             action_integrate(dirs, action).await?;
             Ok(())
         }),
-        Some(Action::Profile(action)) => rt.block_on(async {
-            action_integrate_profile(dirs, action).await?;
+        Some(Action::Profile(action)) => match action.action {
+            ProfileAction::Run(action) => rt.block_on(async {
+                action_integrate_profile(dirs, action).await?;
+                Ok(())
+            }),
+            ProfileAction::Export(action) => action_profile_export(dirs, action),
+            ProfileAction::Import(action) => action_profile_import(dirs, action),
+            action => action_profile(dirs, action),
+        },
+        Some(Action::Group(action)) => action_group(dirs, action.action),
+        Some(Action::Lock(action)) => rt.block_on(async {
+            action_lock(dirs, action).await?;
             Ok(())
         }),
+        Some(Action::Watch(action)) => rt.block_on(async {
+            action_watch(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Uninstall(action)) => action_uninstall(dirs, action),
+        Some(Action::Rollback(action)) => action_rollback(dirs, action),
         Some(Action::Launch(action)) => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
@@ -145,6 +590,10 @@ fn main() -> Result<()> {
             action_lint(dirs, action).await?;
             Ok(())
         }),
+        Some(Action::Cache(action)) => match action.action {
+            CacheAction::Gc(action) => action_cache_gc(dirs, action),
+            CacheAction::Verify(action) => action_cache_verify(dirs, action),
+        },
         None => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
@@ -191,6 +640,12 @@ fn get_pak_path(state: &State, arg: &Option<PathBuf>) -> Result<PathBuf> {
 
 async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
     let mut state = State::init(dirs)?;
+    if action.offline {
+        state.store.set_offline(true);
+    }
+    let policy = action
+        .missing_deps
+        .unwrap_or(state.config.missing_dependencies);
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
 
@@ -200,11 +655,21 @@ async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
         .map(ModSpecification::new)
         .collect::<Vec<_>>();
 
+    let target = IntegrationTarget {
+        history: action.output.is_none().then(|| state.history()),
+        output: action.output,
+        install_hook: !action.no_hook,
+        conflict_report: action.conflict_report,
+    };
+
     resolve_unordered_and_integrate_with_provider_init(
         game_pak_path,
         &mut state,
         &mod_specs,
         action.update,
+        policy,
+        &target,
+        &ConflictRules::default(),
         init_provider,
     )
     .await
@@ -212,56 +677,540 @@ async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
 }
 
 async fn action_integrate_profile(dirs: Dirs, action: ActionIntegrateProfile) -> Result<()> {
+    let profile = action.profile;
     let mut state = State::init(dirs)?;
+    if action.offline {
+        state.store.set_offline(true);
+    }
+    let policy = action
+        .missing_deps
+        .unwrap_or(state.config.missing_dependencies);
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
 
-    let mut mods = Vec::new();
-    state.mod_data.for_each_enabled_mod(&action.profile, |mc| {
-        mods.push(mc.spec.clone());
-    });
+    let mods = state
+        .mod_data
+        .enabled_mods_by_priority(&profile)
+        .into_iter()
+        .map(|mc| mc.spec)
+        .collect::<Vec<_>>();
+    let rules = state.mod_data.profiles[&profile].conflict_overrides.clone();
 
-    resolve_unordered_and_integrate_with_provider_init(
-        game_pak_path,
+    let target = IntegrationTarget {
+        history: action.output.is_none().then(|| state.history()),
+        output: action.output,
+        install_hook: !action.no_hook,
+        conflict_report: action.conflict_report,
+    };
+
+    if action.locked {
+        let lockfile_path = action
+            .lockfile
+            .unwrap_or_else(|| Lockfile::default_path(&state.dirs.config_dir, &profile));
+        let lockfile = Lockfile::read(&lockfile_path)?;
+
+        resolve_locked_and_integrate_with_provider_init(
+            game_pak_path,
+            &mut state,
+            &lockfile,
+            &mods,
+            &target,
+            &rules,
+            init_provider,
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e))
+    } else {
+        resolve_unordered_and_integrate_with_provider_init(
+            game_pak_path,
+            &mut state,
+            &mods,
+            action.update,
+            policy,
+            &target,
+            &rules,
+            init_provider,
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e))
+    }
+}
+
+async fn action_lock(dirs: Dirs, action: ActionLock) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    if action.offline {
+        state.store.set_offline(true);
+    }
+    let policy = action
+        .missing_deps
+        .unwrap_or(state.config.missing_dependencies);
+
+    let mods = state
+        .mod_data
+        .enabled_mods_by_priority(&action.profile)
+        .into_iter()
+        .map(|mc| mc.spec)
+        .collect::<Vec<_>>();
+
+    let lockfile = resolve_and_lock_with_provider_init(
         &mut state,
+        &action.profile,
         &mods,
         action.update,
+        policy,
         init_provider,
     )
     .await
-    .map_err(|e| anyhow!("{}", e))
+    .map_err(|e| anyhow!("{}", e))?;
+
+    let lockfile_path = action
+        .lockfile
+        .unwrap_or_else(|| Lockfile::default_path(&state.dirs.config_dir, &action.profile));
+    lockfile.write(&lockfile_path)?;
+    info!(
+        "locked {} mods to {}",
+        lockfile.mods.len(),
+        lockfile_path.display()
+    );
+    Ok(())
+}
+
+async fn action_watch(dirs: Dirs, action: ActionWatch) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    if action.offline {
+        state.store.set_offline(true);
+    }
+    let policy = action
+        .missing_deps
+        .unwrap_or(state.config.missing_dependencies);
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    debug!(?game_pak_path);
+
+    let mods = state
+        .mod_data
+        .enabled_mods_by_priority(&action.profile)
+        .into_iter()
+        .map(|mc| mc.spec)
+        .collect::<Vec<_>>();
+    let rules = state.mod_data.profiles[&action.profile]
+        .conflict_overrides
+        .clone();
+    let target = IntegrationTarget {
+        output: action.output,
+        install_hook: !action.no_hook,
+        conflict_report: None,
+        // frequent re-integrations while developing would push every other bundle out of the
+        // history
+        history: None,
+    };
+
+    let mut watcher = ModWatcher::new(&mods, Duration::from_millis(action.debounce));
+    if watcher.is_empty() {
+        bail!(
+            "profile {:?} does not contain any local mod files or directories to watch",
+            action.profile
+        );
+    }
+    for spec in watcher.specs() {
+        println!("watching {}", spec.url);
+    }
+
+    loop {
+        match resolve_unordered_and_integrate_with_provider_init(
+            &game_pak_path,
+            &mut state,
+            &mods,
+            false,
+            policy,
+            &target,
+            &rules,
+            init_provider,
+        )
+        .await
+        {
+            Ok(()) => println!("integrated {} mods", mods.len()),
+            // keep watching, the next change may fix the mod
+            Err(e) => println!("integration failed: {e}"),
+        }
+
+        let changes = loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if let Some(changes) = watcher.poll() {
+                break changes;
+            }
+        };
+        for change in changes {
+            println!("{change}");
+        }
+    }
 }
 
 async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
+    if action.list {
+        for lint in LintFactory::all() {
+            println!(
+                "{:<32} {:<8} {:<9} {}",
+                lint.id.name(),
+                lint.default_severity.as_str(),
+                if lint.enabled_by_default {
+                    "default"
+                } else {
+                    "optional"
+                },
+                lint.description
+            );
+        }
+        return Ok(());
+    }
+    let profile = action.profile.unwrap();
+
     let mut state = State::init(dirs)?;
+    if action.offline {
+        state.store.set_offline(true);
+    }
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
 
-    let mut mods = Vec::new();
-    state.mod_data.for_each_mod(&action.profile, |mc| {
-        mods.push(mc.spec.clone());
+    let mut mod_configs = Vec::new();
+    state.mod_data.for_each_mod(&profile, |mc| {
+        mod_configs.push(mc.clone());
     });
+    mod_configs.sort_by_key(|mc| mc.priority);
+    let mods = mod_configs
+        .into_iter()
+        .map(|mc| mc.spec)
+        .collect::<Vec<_>>();
+    let rules = state.mod_data.profiles[&profile].conflict_overrides.clone();
+
+    if !action.suppress.is_empty() {
+        let suppressions = &mut state
+            .mod_data
+            .profiles
+            .get_mut(&profile)
+            .unwrap()
+            .lint_suppressions;
+        for suppression in action.suppress {
+            if !suppressions.contains(&suppression) {
+                suppressions.push(suppression);
+            }
+        }
+        state.mod_data.save()?;
+    }
+    let suppressions = if action.no_suppressions {
+        vec![]
+    } else {
+        state.mod_data.profiles[&profile].lint_suppressions.clone()
+    };
+
+    let mut lints = if action.all {
+        LintId::all()
+    } else {
+        LintId::defaults()
+    };
+    lints.extend(action.enable);
+    for lint in &action.disable {
+        lints.remove(lint);
+    }
 
     let mod_paths = resolve_ordered_with_provider_init(&mut state, &mods, init_provider).await?;
 
-    let report = tokio::task::spawn_blocking(move || {
-        run_lints(
-            &BTreeSet::from([
-                LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
-                LintId::ASSET_REGISTRY_BIN,
-                LintId::CONFLICTING,
-                LintId::EMPTY_ARCHIVE,
-                LintId::OUTDATED_PAK_VERSION,
-                LintId::SHADER_FILES,
-                LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
-                LintId::NON_ASSET_FILES,
-                LintId::SPLIT_ASSET_PAIRS,
-            ]),
+    let mut report = tokio::task::spawn_blocking(move || {
+        run_lints_with_conflict_rules(
+            &lints,
             mods.into_iter().zip(mod_paths).collect(),
             Some(game_pak_path),
+            rules,
         )
     })
     .await??;
-    println!("{:#?}", report);
+    report.suppress(&suppressions);
+    print!("{}", report.format(action.format));
+
+    if let Some(fail_on) = action.fail_on {
+        let failed = report
+            .findings()
+            .iter()
+            .filter(|f| f.severity >= fail_on)
+            .count();
+        if failed > 0 {
+            bail!(
+                "{failed} lint findings of severity {} or higher",
+                fail_on.as_str()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn action_uninstall(dirs: Dirs, action: ActionUninstall) -> Result<()> {
+    let state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+
+    if action.restore_backup {
+        restore_uninstall_backup(&game_pak_path).map_err(|e| anyhow!("{e}"))?;
+        println!("restored GameUserSettings.ini");
+        return Ok(());
+    }
+
+    let mut modio_mods = action.enable.into_iter().collect::<HashSet<_>>();
+    if let Some(profile) = &action.profile {
+        if !state.mod_data.profiles.contains_key(profile) {
+            bail!("profile {profile:?} does not exist");
+        }
+        state.mod_data.for_each_enabled_mod(profile, |mc| {
+            if let Some(modio_id) = state.store.get_mod_info(&mc.spec).and_then(|i| i.modio_id) {
+                modio_mods.insert(modio_id);
+            }
+        });
+    }
+    uninstall(&game_pak_path, modio_mods).map_err(|e| anyhow!("{e}"))?;
+    println!("uninstalled mods");
+    Ok(())
+}
+
+fn action_rollback(dirs: Dirs, action: ActionRollback) -> Result<()> {
+    let state = State::init(dirs)?;
+    let history = state.history();
+
+    if action.list {
+        for (n, entry) in history.list()?.iter().enumerate() {
+            println!(
+                "{n}: integrated {} with {} mods",
+                entry.age(),
+                entry.mods.len()
+            );
+            for m in &entry.mods {
+                println!("    {} ({})", m.name, m.url);
+            }
+        }
+        return Ok(());
+    }
+
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    let path_mod_pak = DRGInstallation::from_pak_path(game_pak_path)?
+        .paks_path()
+        .join("mods_P.pak");
+    let entry = history.get(action.n)?;
+    history.restore(&entry, &path_mod_pak)?;
+    println!(
+        "restored bundle integrated {} with {} mods",
+        entry.age(),
+        entry.mods.len()
+    );
+    Ok(())
+}
+
+fn action_profile_export(dirs: Dirs, action: ActionProfileExport) -> Result<()> {
+    let state = State::init(dirs)?;
+    let shared = SharedProfile::export(&state.mod_data, &action.profile)?;
+    let exported = if action.code {
+        shared.to_share_code()
+    } else {
+        shared.to_json()
+    };
+    match action.output {
+        Some(path) => std::fs::write(path, exported)?,
+        None => println!("{exported}"),
+    }
+    Ok(())
+}
+
+fn action_profile_import(dirs: Dirs, action: ActionProfileImport) -> Result<()> {
+    let input = if action.input == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else if Path::new(&action.input).is_file() {
+        std::fs::read_to_string(&action.input)?
+    } else {
+        action.input
+    };
+    let shared = SharedProfile::parse(&input)?;
+
+    let mut state = State::init(dirs)?;
+    let name = shared.import(&mut state.mod_data, action.on_conflict);
+    state.mod_data.save()?;
+    println!("imported profile {name:?}");
+    Ok(())
+}
+
+fn action_profile(dirs: Dirs, action: ProfileAction) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let mod_data = &mut **state.mod_data;
+    match action {
+        ProfileAction::Run(_) | ProfileAction::Export(_) | ProfileAction::Import(_) => {
+            unreachable!()
+        }
+        ProfileAction::List => {
+            for (name, profile) in &mod_data.profiles {
+                let active = if *name == mod_data.active_profile {
+                    '*'
+                } else {
+                    ' '
+                };
+                println!("{active} {name} ({} entries)", profile.mods.len());
+            }
+            return Ok(());
+        }
+        ProfileAction::Create(action) => mod_data.create_profile(&action.name)?,
+        ProfileAction::Rename(action) => mod_data.rename_profile(&action.name, &action.new_name)?,
+        ProfileAction::Delete(action) => mod_data.delete_profile(&action.name)?,
+        ProfileAction::SetActive(action) => mod_data.set_active_profile(&action.name)?,
+        ProfileAction::Add(action) => {
+            for entry in &action.entries {
+                if action.group && !mod_data.groups.contains_key(entry) {
+                    bail!("group {entry:?} does not exist");
+                }
+                let profile = mod_data.get_profile_mut(&action.profile)?;
+                if profile.mods.iter().any(|m| m.is(entry, action.group)) {
+                    bail!("{entry:?} is already in profile {:?}", action.profile);
+                }
+                profile.mods.push(if action.group {
+                    ModOrGroup::Group {
+                        group_name: entry.clone(),
+                        enabled: !action.disabled,
+                    }
+                } else {
+                    ModOrGroup::Individual(ModConfig {
+                        spec: ModSpecification::new(entry.clone()),
+                        required: action.required,
+                        enabled: !action.disabled,
+                        priority: 0,
+                    })
+                });
+            }
+        }
+        ProfileAction::Remove(action) => edit_profile_entries(mod_data, &action, |mods, i| {
+            mods.remove(i);
+        })?,
+        ProfileAction::Enable(action) => {
+            edit_profile_entries(mod_data, &action, |mods, i| mods[i].set_enabled(true))?
+        }
+        ProfileAction::Disable(action) => {
+            edit_profile_entries(mod_data, &action, |mods, i| mods[i].set_enabled(false))?
+        }
+        ProfileAction::Require(action) | ProfileAction::Unrequire(action) if action.group => {
+            bail!("only individual mods can be marked as required")
+        }
+        ProfileAction::Require(action) => edit_profile_entries(mod_data, &action, |mods, i| {
+            if let ModOrGroup::Individual(mc) = &mut mods[i] {
+                mc.required = true;
+            }
+        })?,
+        ProfileAction::Unrequire(action) => edit_profile_entries(mod_data, &action, |mods, i| {
+            if let ModOrGroup::Individual(mc) = &mut mods[i] {
+                mc.required = false;
+            }
+        })?,
+    }
+    state.mod_data.save()?;
+    Ok(())
+}
+
+/// Apply `edit` to each mod or group of `action.entries` in the profile, given its index.
+fn edit_profile_entries(
+    mod_data: &mut ModData,
+    action: &ActionProfileEntries,
+    mut edit: impl FnMut(&mut Vec<ModOrGroup>, usize),
+) -> Result<()> {
+    let profile = mod_data.get_profile_mut(&action.profile)?;
+    for entry in &action.entries {
+        let Some(index) = profile.mods.iter().position(|m| m.is(entry, action.group)) else {
+            bail!("{entry:?} is not in profile {:?}", action.profile);
+        };
+        edit(&mut profile.mods, index);
+    }
+    Ok(())
+}
+
+fn action_group(dirs: Dirs, action: GroupAction) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let mod_data = &mut **state.mod_data;
+    match action {
+        GroupAction::List => {
+            for (name, group) in &mod_data.groups {
+                let profiles = mod_data.profiles_using_group(name);
+                if profiles.is_empty() {
+                    println!("{name}");
+                } else {
+                    println!("{name} (in {})", profiles.join(", "));
+                }
+                for mc in &group.mods {
+                    let enabled = if mc.enabled { '+' } else { '-' };
+                    let required = if mc.required { " (required)" } else { "" };
+                    println!("  {enabled} {}{required}", mc.spec.url);
+                }
+            }
+            return Ok(());
+        }
+        GroupAction::Create(action) => mod_data.create_group(&action.name)?,
+        GroupAction::Rename(action) => mod_data.rename_group(&action.name, &action.new_name)?,
+        GroupAction::Delete(action) => mod_data.delete_group(&action.name, action.force)?,
+        GroupAction::Add(action) => {
+            let group = mod_data.get_group_mut(&action.group)?;
+            for url in action.mods {
+                if group.get_mod_mut(&url).is_some() {
+                    bail!("{url:?} is already in group {:?}", action.group);
+                }
+                group.mods.push(ModConfig {
+                    spec: ModSpecification::new(url),
+                    required: action.required,
+                    enabled: !action.disabled,
+                    priority: 0,
+                });
+            }
+        }
+        GroupAction::Remove(action) => {
+            let group = mod_data.get_group_mut(&action.group)?;
+            for url in &action.mods {
+                let Some(index) = group.mods.iter().position(|mc| mc.spec.url == *url) else {
+                    bail!("{url:?} is not in group {:?}", action.group);
+                };
+                group.mods.remove(index);
+            }
+        }
+        GroupAction::Enable(action) => edit_group_mods(mod_data, &action, |mc| mc.enabled = true)?,
+        GroupAction::Disable(action) => {
+            edit_group_mods(mod_data, &action, |mc| mc.enabled = false)?
+        }
+        GroupAction::Require(action) => {
+            edit_group_mods(mod_data, &action, |mc| mc.required = true)?
+        }
+        GroupAction::Unrequire(action) => {
+            edit_group_mods(mod_data, &action, |mc| mc.required = false)?
+        }
+    }
+    state.mod_data.save()?;
+    Ok(())
+}
+
+fn edit_group_mods(
+    mod_data: &mut ModData,
+    action: &ActionGroupMods,
+    edit: impl Fn(&mut ModConfig),
+) -> Result<()> {
+    let group = mod_data.get_group_mut(&action.group)?;
+    for url in &action.mods {
+        let Some(mc) = group.get_mod_mut(url) else {
+            bail!("{url:?} is not in group {:?}", action.group);
+        };
+        edit(mc);
+    }
+    Ok(())
+}
+
+fn action_cache_gc(dirs: Dirs, action: ActionCacheGc) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let stats = gc_cache_with_provider_init(&mut state, action.keep, action.dry_run, init_provider)
+        .map_err(|e| anyhow!("{}", e))?;
+    println!("{stats}");
+    Ok(())
+}
+
+fn action_cache_verify(dirs: Dirs, _action: ActionCacheVerify) -> Result<()> {
+    let state = State::init(dirs)?;
+    let stats = state.store.verify_blobs()?;
+    for name in &stats.evicted {
+        println!("evicted corrupt blob {name}");
+    }
+    println!("{stats}");
     Ok(())
 }
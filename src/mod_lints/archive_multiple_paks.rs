@@ -2,7 +2,23 @@ use std::collections::BTreeSet;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const ARCHIVE_WITH_MULTIPLE_PAKS: Self = LintId::new("archive_with_multiple_paks");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
+        description: "Mods containing multiple pak files",
+        message: "archive contains multiple paks, only the first one is used",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<ArchiveMultiplePaksLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct ArchiveMultiplePaksLint;
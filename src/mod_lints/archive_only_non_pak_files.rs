@@ -2,7 +2,23 @@ use std::collections::BTreeSet;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const ARCHIVE_WITH_ONLY_NON_PAK_FILES: Self = LintId::new("archive_only_non_pak_files");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES,
+        description: "Mods containing only non-pak files",
+        message: "archive only contains non-pak files",
+        default_severity: Severity::Error,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<ArchiveOnlyNonPakFilesLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct ArchiveOnlyNonPakFilesLint;
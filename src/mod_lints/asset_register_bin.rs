@@ -2,7 +2,23 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const ASSET_REGISTRY_BIN: Self = LintId::new("asset_registry_bin");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::ASSET_REGISTRY_BIN,
+        description: "Mods containing AssetRegistry.bin",
+        message: "mod contains an AssetRegistry.bin",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<AssetRegisterBinLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct AssetRegisterBinLint;
@@ -0,0 +1,370 @@
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::{BufReader, Cursor, Read};
+use std::path::PathBuf;
+
+use fs_err as fs;
+use serde::Serialize;
+use tracing::trace;
+
+use crate::integrate::PCB_PATH;
+use crate::providers::ModSpecification;
+
+use super::{
+    InvalidGamePathSnafu, Lint, LintCtxt, LintError, LintFactory, LintFinding, LintId, LintOutput,
+    Severity,
+};
+
+impl LintId {
+    pub const ASSET_VERSIONS: Self = LintId::new("asset_versions");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::ASSET_VERSIONS,
+        description: "Mods containing assets cooked for a different engine version",
+        message: "mod contains assets cooked for a different engine version",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: true,
+        run: super::run_lint::<AssetVersionsLint>,
+    }
+}
+
+#[derive(Default)]
+pub struct AssetVersionsLint;
+
+const PACKAGE_FILE_TAG: u32 = 0x9E2A83C1;
+
+/// Versions an asset was saved with, read from the start of its package summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PackageVersions {
+    legacy_file_version: i32,
+    file_version_ue4: i32,
+    file_version_licensee_ue4: i32,
+    custom_versions: BTreeMap<String, i32>,
+}
+
+fn read_i32<R: Read>(reader: &mut R) -> std::io::Result<i32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_guid<R: Read>(reader: &mut R) -> std::io::Result<String> {
+    let mut guid = String::new();
+    for _ in 0..4 {
+        guid.push_str(&format!("{:08X}", read_i32(reader)? as u32));
+    }
+    Ok(guid)
+}
+
+fn skip_fstring<R: Read>(reader: &mut R) -> std::io::Result<()> {
+    let len = read_i32(reader)?;
+    // negative lengths are UTF-16
+    let bytes = if len < 0 {
+        -(len as i64) * 2
+    } else {
+        len as i64
+    };
+    std::io::copy(&mut reader.take(bytes as u64), &mut std::io::sink())?;
+    Ok(())
+}
+
+impl PackageVersions {
+    fn read<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+
+        if read_i32(reader)? as u32 != PACKAGE_FILE_TAG {
+            return Err(invalid("not a package file"));
+        }
+        let legacy_file_version = read_i32(reader)?;
+        if legacy_file_version >= 0 || legacy_file_version < -8 {
+            return Err(invalid("unsupported legacy file version"));
+        }
+        if legacy_file_version != -4 {
+            let _legacy_ue3_version = read_i32(reader)?;
+        }
+        let file_version_ue4 = read_i32(reader)?;
+        if legacy_file_version <= -8 {
+            let _file_version_ue5 = read_i32(reader)?;
+        }
+        let file_version_licensee_ue4 = read_i32(reader)?;
+
+        let mut custom_versions = BTreeMap::new();
+        if legacy_file_version <= -2 {
+            let count = read_i32(reader)?;
+            for _ in 0..count {
+                let (key, version) = match legacy_file_version {
+                    // enum based custom versions
+                    -2 => {
+                        let tag = read_i32(reader)?;
+                        (format!("{tag:08X}"), read_i32(reader)?)
+                    }
+                    // guid based custom versions with friendly name
+                    -5..=-3 => {
+                        let guid = read_guid(reader)?;
+                        let version = read_i32(reader)?;
+                        skip_fstring(reader)?;
+                        (guid, version)
+                    }
+                    _ => (read_guid(reader)?, read_i32(reader)?),
+                };
+                custom_versions.insert(key, version);
+            }
+        }
+
+        Ok(Self {
+            legacy_file_version,
+            file_version_ue4,
+            file_version_licensee_ue4,
+            custom_versions,
+        })
+    }
+
+    /// Unversioned packages are loaded with the versions of the running engine.
+    fn is_unversioned(&self) -> bool {
+        self.file_version_ue4 == 0 && self.file_version_licensee_ue4 == 0
+    }
+
+    /// Differences of `self` from the `game` versions. Custom versions are only compared if both
+    /// packages use them.
+    fn mismatches(&self, game: &PackageVersions) -> BTreeSet<VersionMismatch> {
+        let mut mismatches = BTreeSet::new();
+        if self.legacy_file_version != game.legacy_file_version {
+            mismatches.insert(VersionMismatch::LegacyFileVersion {
+                expected: game.legacy_file_version,
+                found: self.legacy_file_version,
+            });
+        }
+        if !self.is_unversioned() && !game.is_unversioned() {
+            if self.file_version_ue4 != game.file_version_ue4 {
+                mismatches.insert(VersionMismatch::FileVersion {
+                    expected: game.file_version_ue4,
+                    found: self.file_version_ue4,
+                });
+            }
+            if self.file_version_licensee_ue4 != game.file_version_licensee_ue4 {
+                mismatches.insert(VersionMismatch::LicenseeVersion {
+                    expected: game.file_version_licensee_ue4,
+                    found: self.file_version_licensee_ue4,
+                });
+            }
+        }
+        for (guid, found) in &self.custom_versions {
+            if let Some(expected) = game.custom_versions.get(guid)
+                && expected != found
+            {
+                mismatches.insert(VersionMismatch::CustomVersion {
+                    guid: guid.clone(),
+                    expected: *expected,
+                    found: *found,
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum VersionMismatch {
+    LegacyFileVersion {
+        expected: i32,
+        found: i32,
+    },
+    FileVersion {
+        expected: i32,
+        found: i32,
+    },
+    LicenseeVersion {
+        expected: i32,
+        found: i32,
+    },
+    CustomVersion {
+        guid: String,
+        expected: i32,
+        found: i32,
+    },
+}
+
+impl Display for VersionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionMismatch::LegacyFileVersion { expected, found } => write!(
+                f,
+                "legacy file version {found} does not match the game's {expected}"
+            ),
+            VersionMismatch::FileVersion { expected, found } => write!(
+                f,
+                "package file version {found} does not match the game's {expected}"
+            ),
+            VersionMismatch::LicenseeVersion { expected, found } => write!(
+                f,
+                "licensee version {found} does not match the game's {expected}"
+            ),
+            VersionMismatch::CustomVersion {
+                guid,
+                expected,
+                found,
+            } => write!(
+                f,
+                "custom version {guid} is {found}, the game uses {expected}"
+            ),
+        }
+    }
+}
+
+impl Lint for AssetVersionsLint {
+    /// Per mod, assets and how their versions differ from the game's.
+    type Output = BTreeMap<ModSpecification, BTreeMap<String, BTreeSet<VersionMismatch>>>;
+
+    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError> {
+        let Some(game_pak_path) = &lcx.fsd_pak_path else {
+            InvalidGamePathSnafu.fail()?
+        };
+
+        let mut reader = BufReader::new(fs::File::open(game_pak_path)?);
+        let game_pak = repak::PakBuilder::new().reader(&mut reader)?;
+        let game_versions = PackageVersions::read(&mut Cursor::new(
+            game_pak.get(&format!("{PCB_PATH}.uasset"), &mut reader)?,
+        ))?;
+        trace!(?game_versions);
+
+        let mut version_mismatches = BTreeMap::new();
+
+        lcx.for_each_mod(
+            |mod_spec, pak_read_seek, pak_reader| {
+                let mount = PathBuf::from(pak_reader.mount_point());
+                for path in pak_reader.files() {
+                    if !(path.ends_with(".uasset") || path.ends_with(".umap")) {
+                        continue;
+                    }
+                    let normalized_path = mount
+                        .join(&path)
+                        .strip_prefix("../../../")?
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                        .to_ascii_lowercase();
+
+                    let data = pak_reader.get(&path, pak_read_seek)?;
+                    let mismatches = match PackageVersions::read(&mut Cursor::new(data)) {
+                        Ok(versions) => versions.mismatches(&game_versions),
+                        Err(e) => {
+                            trace!("failed to read package summary of {normalized_path}: {e}");
+                            continue;
+                        }
+                    };
+                    if !mismatches.is_empty() {
+                        version_mismatches
+                            .entry(mod_spec.clone())
+                            .or_insert_with(BTreeMap::new)
+                            .insert(normalized_path, mismatches);
+                    }
+                }
+                Ok(())
+            },
+            None::<fn(ModSpecification)>,
+            None::<fn(ModSpecification)>,
+            None::<fn(ModSpecification)>,
+        )?;
+
+        Ok(version_mismatches)
+    }
+}
+
+impl LintOutput for BTreeMap<ModSpecification, BTreeMap<String, BTreeSet<VersionMismatch>>> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        let mut findings = vec![];
+        for (spec, assets) in self {
+            let mut per_mismatch: BTreeMap<&VersionMismatch, Vec<String>> = BTreeMap::new();
+            for (path, mismatches) in assets {
+                for mismatch in mismatches {
+                    per_mismatch.entry(mismatch).or_default().push(path.clone());
+                }
+            }
+            for (mismatch, paths) in per_mismatch {
+                findings.push(
+                    LintFinding::new(lint, vec![spec.clone()], mismatch.to_string())
+                        .with_paths(paths),
+                );
+            }
+        }
+        findings
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(
+            self.iter()
+                .map(|(spec, assets)| (&spec.url, assets))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec, _| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn summary(legacy: i32, ue4: i32, licensee: i32, custom: &[([u32; 4], i32)]) -> Vec<u8> {
+        let mut buf = vec![];
+        for i in [PACKAGE_FILE_TAG as i32, legacy, 864, ue4, licensee] {
+            buf.extend(i.to_le_bytes());
+        }
+        buf.extend((custom.len() as i32).to_le_bytes());
+        for (guid, version) in custom {
+            for part in guid {
+                buf.extend(part.to_le_bytes());
+            }
+            buf.extend(version.to_le_bytes());
+        }
+        buf
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let read = |buf: Vec<u8>| PackageVersions::read(&mut Cursor::new(buf)).unwrap();
+        let game = read(summary(
+            -7,
+            522,
+            0,
+            &[([1, 2, 3, 4], 10), ([5, 6, 7, 8], 3)],
+        ));
+        assert_eq!(game.custom_versions["00000001000000020000000300000004"], 10);
+
+        let same = read(summary(-7, 522, 0, &[([1, 2, 3, 4], 10)]));
+        assert!(same.mismatches(&game).is_empty());
+
+        let unversioned = read(summary(-7, 0, 0, &[]));
+        assert!(unversioned.mismatches(&game).is_empty());
+
+        let different = read(summary(-7, 518, 0, &[([5, 6, 7, 8], 2), ([9, 9, 9, 9], 1)]));
+        assert_eq!(
+            different.mismatches(&game),
+            [
+                VersionMismatch::FileVersion {
+                    expected: 522,
+                    found: 518
+                },
+                VersionMismatch::CustomVersion {
+                    guid: "00000005000000060000000700000008".into(),
+                    expected: 3,
+                    found: 2
+                },
+            ]
+            .into()
+        );
+
+        assert!(PackageVersions::read(&mut Cursor::new(vec![0; 32])).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufReader, Cursor};
+use std::path::PathBuf;
+
+use fs_err as fs;
+use tracing::trace;
+use unreal_asset::engine_version::EngineVersion;
+use unreal_asset::AssetBuilder;
+
+use crate::providers::ModSpecification;
+
+use super::{
+    InvalidGamePathSnafu, Lint, LintCtxt, LintError, LintFactory, LintFinding, LintId, LintOutput,
+    Severity,
+};
+
+impl LintId {
+    pub const BROKEN_REFERENCES: Self = LintId::new("broken_references");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::BROKEN_REFERENCES,
+        description: "Mods referencing missing packages",
+        message: "mod references missing packages",
+        default_severity: Severity::Error,
+        enabled_by_default: true,
+        requires_game_pak: true,
+        run: super::run_lint::<BrokenReferencesLint>,
+    }
+}
+
+#[derive(Default)]
+pub struct BrokenReferencesLint;
+
+/// Lowercase package name such as `/game/foo/bar` of an asset at the lowercase `path` relative to
+/// the pak root, e.g. `fsd/content/foo/bar.uasset`.
+fn package_name(path: &str) -> Option<String> {
+    let stem = path
+        .strip_suffix(".uasset")
+        .or_else(|| path.strip_suffix(".umap"))?;
+    if let Some(rest) = stem.strip_prefix("fsd/content/") {
+        Some(format!("/game/{rest}"))
+    } else if let Some(rest) = stem.strip_prefix("engine/content/") {
+        Some(format!("/engine/{rest}"))
+    } else {
+        // plugin content is mounted under the name of the plugin
+        let (_, rest) = stem.split_once("/plugins/")?;
+        let (plugin_path, rest) = rest.split_once("/content/")?;
+        let plugin = plugin_path.rsplit('/').next()?;
+        Some(format!("/{plugin}/{rest}"))
+    }
+}
+
+fn package_root(package: &str) -> &str {
+    package
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default()
+}
+
+fn normalize_pak_path(mount: &str, path: &str) -> Option<String> {
+    PathBuf::from(mount)
+        .join(path)
+        .strip_prefix("../../../")
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/").to_ascii_lowercase())
+}
+
+impl Lint for BrokenReferencesLint {
+    /// Per mod, unresolved packages and the assets referencing them.
+    type Output = BTreeMap<ModSpecification, BTreeMap<String, BTreeSet<String>>>;
+
+    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError> {
+        let Some(game_pak_path) = &lcx.fsd_pak_path else {
+            InvalidGamePathSnafu.fail()?
+        };
+
+        let mut reader = BufReader::new(fs::File::open(game_pak_path)?);
+        let game_pak = repak::PakBuilder::new().reader(&mut reader)?;
+        let mut packages = game_pak
+            .files()
+            .iter()
+            .filter_map(|path| normalize_pak_path(game_pak.mount_point(), path))
+            .filter_map(|path| package_name(&path))
+            .collect::<BTreeSet<_>>();
+
+        lcx.for_each_mod_file(|_, _, _, _, normalized_path| {
+            packages.extend(package_name(&normalized_path));
+            Ok(())
+        })?;
+
+        // references into mount points neither the game nor any mod provides (e.g. /Script/) can't
+        // be checked
+        let roots = packages
+            .iter()
+            .map(|p| package_root(p).to_string())
+            .collect::<BTreeSet<_>>();
+
+        let mut broken_references = BTreeMap::new();
+
+        lcx.for_each_mod(
+            |mod_spec, pak_read_seek, pak_reader| {
+                let files = pak_reader
+                    .files()
+                    .into_iter()
+                    .filter_map(|path| {
+                        normalize_pak_path(pak_reader.mount_point(), &path).map(|n| (n, path))
+                    })
+                    .collect::<BTreeMap<_, _>>();
+
+                for (normalized_path, path) in &files {
+                    let Some(uexp_path) = normalized_path
+                        .strip_suffix(".uasset")
+                        .or_else(|| normalized_path.strip_suffix(".umap"))
+                        .map(|stem| format!("{stem}.uexp"))
+                        .and_then(|uexp| files.get(&uexp))
+                    else {
+                        continue;
+                    };
+
+                    let uasset = pak_reader.get(path, pak_read_seek)?;
+                    let uexp = pak_reader.get(uexp_path, pak_read_seek)?;
+                    let asset =
+                        match AssetBuilder::new(Cursor::new(uasset), EngineVersion::VER_UE4_27)
+                            .bulk(Cursor::new(uexp))
+                            .skip_data(true)
+                            .build()
+                        {
+                            Ok(asset) => asset,
+                            Err(e) => {
+                                trace!("failed to parse {normalized_path}: {e}");
+                                continue;
+                            }
+                        };
+
+                    let imported = asset
+                        .imports
+                        .iter()
+                        .filter(|import| import.outer_index.index == 0)
+                        .map(|import| import.object_name.get_content(|name| name.to_string()));
+                    let soft = asset.soft_package_reference_list.iter().flatten().cloned();
+
+                    for reference in imported.chain(soft) {
+                        let reference = reference.to_ascii_lowercase();
+                        let package = reference.split('.').next().unwrap_or_default();
+                        if !roots.contains(package_root(package)) || packages.contains(package) {
+                            continue;
+                        }
+                        broken_references
+                            .entry(mod_spec.clone())
+                            .or_insert_with(BTreeMap::new)
+                            .entry(package.to_string())
+                            .or_insert_with(BTreeSet::new)
+                            .insert(normalized_path.clone());
+                    }
+                }
+
+                Ok(())
+            },
+            None::<fn(ModSpecification)>,
+            None::<fn(ModSpecification)>,
+            None::<fn(ModSpecification)>,
+        )?;
+
+        trace!("broken_references:\n{:#?}", broken_references);
+
+        Ok(broken_references)
+    }
+}
+
+impl LintOutput for BTreeMap<ModSpecification, BTreeMap<String, BTreeSet<String>>> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        self.iter()
+            .flat_map(|(spec, references)| {
+                references.iter().map(|(package, paths)| {
+                    LintFinding::new(
+                        lint,
+                        vec![spec.clone()],
+                        format!("references {package} which is neither in the game nor in any mod"),
+                    )
+                    .with_paths(paths.iter().cloned())
+                })
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(
+            self.iter()
+                .map(|(spec, references)| (&spec.url, references))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec, _| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(
+            package_name("fsd/content/ui/foo.uasset").as_deref(),
+            Some("/game/ui/foo")
+        );
+        assert_eq!(
+            package_name("engine/content/a/b.umap").as_deref(),
+            Some("/engine/a/b")
+        );
+        assert_eq!(
+            package_name("engine/plugins/runtime/paper2d/content/x.uasset").as_deref(),
+            Some("/paper2d/x")
+        );
+        assert_eq!(package_name("fsd/content/ui/foo.uexp"), None);
+        assert_eq!(package_root("/game/ui/foo"), "game");
+    }
+}
@@ -1,45 +1,80 @@
+use std::any::Any;
 use std::collections::BTreeMap;
 
-use indexmap::IndexSet;
-
+use crate::conflicts::{find_conflicts, ConflictReport};
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintFactory, LintFinding, LintId, LintOutput, Severity};
+
+impl LintId {
+    pub const CONFLICTING: Self = LintId::new("conflicting");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::CONFLICTING,
+        description: "Mods containing conflicting files",
+        message: "file is contained in more than one mod",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<ConflictingModsLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct ConflictingModsLint;
 
-const CONFLICTING_MODS_LINT_WHITELIST: [&str; 1] = ["fsd/content/_interop"];
-
 impl Lint for ConflictingModsLint {
-    type Output = BTreeMap<String, IndexSet<ModSpecification>>;
+    type Output = ConflictReport;
 
     fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError> {
-        let mut per_path_modifiers = BTreeMap::new();
+        let mods = lcx.mods.iter().map(|(spec, _)| spec).collect::<Vec<_>>();
+        let mut per_path_modifiers: BTreeMap<String, Vec<usize>> = BTreeMap::new();
 
         lcx.for_each_mod_file(|mod_spec, _, _, _, normalized_path| {
-            per_path_modifiers
-                .entry(normalized_path)
-                .and_modify(|modifiers: &mut IndexSet<ModSpecification>| {
-                    modifiers.insert(mod_spec.clone());
-                })
-                .or_insert_with(|| [mod_spec.clone()].into());
+            let index = mods.iter().position(|m| **m == mod_spec).unwrap();
+            let modifiers = per_path_modifiers.entry(normalized_path).or_default();
+            if !modifiers.contains(&index) {
+                modifiers.push(index);
+            }
             Ok(())
         })?;
 
-        let conflicting_mods = per_path_modifiers
-            .into_iter()
-            .filter(|(p, _)| {
-                for whitelisted_path in CONFLICTING_MODS_LINT_WHITELIST {
-                    if p.starts_with(whitelisted_path) {
-                        return false;
-                    }
-                }
-                true
+        Ok(find_conflicts(
+            &per_path_modifiers,
+            &mods,
+            &lcx.conflict_rules,
+        ))
+    }
+}
+
+impl LintOutput for ConflictReport {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        self.iter()
+            .map(|(path, conflict)| {
+                LintFinding::new(
+                    lint,
+                    conflict.mods.clone(),
+                    format!(
+                        "{}, using the copy of {}",
+                        lint.message, conflict.winner.url
+                    ),
+                )
+                .with_paths([path.clone()])
             })
-            .filter(|(_, modifiers)| modifiers.len() > 1)
-            .collect::<BTreeMap<String, IndexSet<ModSpecification>>>();
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|_, conflict| conflict.mods.iter().all(keep));
+    }
 
-        Ok(conflicting_mods)
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 }
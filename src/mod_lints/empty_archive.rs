@@ -2,7 +2,23 @@ use std::collections::BTreeSet;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const EMPTY_ARCHIVE: Self = LintId::new("empty_archive");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::EMPTY_ARCHIVE,
+        description: "Mods containing empty archives",
+        message: "archive is empty",
+        default_severity: Severity::Error,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<EmptyArchiveLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct EmptyArchiveLint;
@@ -0,0 +1,122 @@
+use std::any::Any;
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::integrate::{deferred_asset_paths, integration_file_paths};
+use crate::providers::ModSpecification;
+
+use super::{Lint, LintCtxt, LintError, LintFactory, LintFinding, LintId, LintOutput, Severity};
+
+impl LintId {
+    pub const INTEGRATION_PATHS: Self = LintId::new("integration_paths");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::INTEGRATION_PATHS,
+        description: "Mods containing assets patched or replaced by mint",
+        message: "mod contains assets patched or replaced by mint",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<IntegrationPathsLint>,
+    }
+}
+
+#[derive(Default)]
+pub struct IntegrationPathsLint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationPath {
+    /// Asset mint patches during integration, the mod's copy is patched instead of the game's.
+    Patched,
+    /// File mint writes from its own integration assets, the mod's copy is overwritten.
+    Replaced,
+}
+
+impl Lint for IntegrationPathsLint {
+    type Output = BTreeMap<ModSpecification, BTreeMap<String, IntegrationPath>>;
+
+    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError> {
+        let deferred = deferred_asset_paths()
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>();
+        let integration_files = integration_file_paths()
+            .map(|path| path.to_ascii_lowercase())
+            .collect::<HashSet<_>>();
+
+        let mut integration_path_mods = BTreeMap::new();
+
+        lcx.for_each_mod_file(|mod_spec, _, _, _, normalized_path| {
+            let without_ext = normalized_path
+                .rsplit_once('.')
+                .map_or(normalized_path.as_str(), |(path, _)| path);
+            let kind = if deferred.iter().any(|path| path == without_ext) {
+                IntegrationPath::Patched
+            } else if integration_files.contains(&normalized_path) {
+                IntegrationPath::Replaced
+            } else {
+                return Ok(());
+            };
+            integration_path_mods
+                .entry(mod_spec)
+                .or_insert_with(BTreeMap::new)
+                .insert(normalized_path, kind);
+            Ok(())
+        })?;
+
+        Ok(integration_path_mods)
+    }
+}
+
+impl LintOutput for BTreeMap<ModSpecification, BTreeMap<String, IntegrationPath>> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        let mut findings = vec![];
+        for (spec, paths) in self {
+            for (kind, message) in [
+                (
+                    IntegrationPath::Patched,
+                    "mod overrides assets mint patches during integration, mint will patch the \
+                     mod's copy instead of the game's which may break the mod or the patches",
+                ),
+                (
+                    IntegrationPath::Replaced,
+                    "mod contains files mint writes during integration, they will be \
+                     overwritten by mint's own copies",
+                ),
+            ] {
+                let kind_paths = paths
+                    .iter()
+                    .filter(|(_, k)| **k == kind)
+                    .map(|(path, _)| path.clone())
+                    .collect::<Vec<_>>();
+                if !kind_paths.is_empty() {
+                    findings.push(
+                        LintFinding::new(lint, vec![spec.clone()], message.to_string())
+                            .with_paths(kind_paths),
+                    );
+                }
+            }
+        }
+        findings
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(
+            self.iter()
+                .map(|(spec, paths)| (&spec.url, paths))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec, _| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
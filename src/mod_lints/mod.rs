@@ -1,14 +1,19 @@
 mod archive_multiple_paks;
 mod archive_only_non_pak_files;
 mod asset_register_bin;
+mod asset_versions;
+mod broken_references;
 mod conflicting_mods;
 mod empty_archive;
+mod integration_paths;
 mod non_asset_files;
 mod outdated_pak_version;
+mod report;
 mod shader_files;
 mod split_asset_pairs;
 mod unmodified_game_assets;
 
+use std::any::Any;
 use std::collections::{BTreeMap, BTreeSet};
 use std::io::{BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
@@ -16,20 +21,15 @@ use std::path::{Path, PathBuf};
 use fs_err as fs;
 use indexmap::IndexSet;
 use repak::PakReader;
+use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
-use tracing::trace;
-
-use self::archive_multiple_paks::ArchiveMultiplePaksLint;
-use self::archive_only_non_pak_files::ArchiveOnlyNonPakFilesLint;
-use self::asset_register_bin::AssetRegisterBinLint;
-use self::empty_archive::EmptyArchiveLint;
-use self::non_asset_files::NonAssetFilesLint;
-use self::outdated_pak_version::OutdatedPakVersionLint;
-use self::shader_files::ShaderFilesLint;
+use tracing::{trace, warn};
+
+pub use self::asset_versions::VersionMismatch;
+pub use self::integration_paths::IntegrationPath;
+pub use self::report::{LintFinding, LintFormat, Severity};
 pub use self::split_asset_pairs::SplitAssetPair;
-use self::split_asset_pairs::SplitAssetPairsLint;
-use self::unmodified_game_assets::UnmodifiedGameAssetsLint;
-use crate::mod_lints::conflicting_mods::ConflictingModsLint;
+use crate::conflicts::ConflictRules;
 use crate::providers::{ModSpecification, ReadSeek};
 
 #[derive(Debug, Snafu)]
@@ -48,11 +48,15 @@ pub enum LintError {
     OnlyNonPakFiles,
     #[snafu(display("some lints require specifying a valid game pak path"))]
     InvalidGamePath,
+    #[snafu(display("unknown lint `{name}`"))]
+    UnknownLint { name: &'static str },
 }
 
 pub struct LintCtxt {
+    /// Mods in ascending order of precedence.
     pub(crate) mods: IndexSet<(ModSpecification, PathBuf)>,
     pub(crate) fsd_pak_path: Option<PathBuf>,
+    pub(crate) conflict_rules: ConflictRules,
 }
 
 impl LintCtxt {
@@ -61,7 +65,17 @@ impl LintCtxt {
         fsd_pak_path: Option<PathBuf>,
     ) -> Result<Self, LintError> {
         trace!("LintCtxt::init");
-        Ok(Self { mods, fsd_pak_path })
+        Ok(Self {
+            mods,
+            fsd_pak_path,
+            conflict_rules: Default::default(),
+        })
+    }
+
+    /// Resolve conflicts the same way integration does with `rules`.
+    pub fn with_conflict_rules(mut self, rules: ConflictRules) -> Self {
+        self.conflict_rules = rules;
+        self
     }
 
     pub fn for_each_mod<F, EmptyArchiveHandler, OnlyNonPakFilesHandler, MultiplePakFilesHandler>(
@@ -208,66 +222,171 @@ pub(crate) fn lint_get_all_files_from_data(
 }
 
 pub trait Lint {
-    type Output;
+    type Output: LintOutput;
 
     fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError>;
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// Type-erased output of a [`Lint`], stored in a [`LintReport`].
+pub trait LintOutput: std::fmt::Debug + Send + Sync + Any {
+    /// Individual findings, reported with the severity and message of `lint`.
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding>;
+    /// Raw output for machine-readable reports, mods are keyed by URL.
+    fn to_json(&self) -> serde_json::Value;
+    /// Remove the findings of mods for which `keep` returns false.
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool);
+    fn as_any(&self) -> &dyn Any;
+}
+
+#[derive(Clone)]
+pub struct LintFactory {
+    pub id: LintId,
+    /// Short description shown when selecting lints.
+    pub description: &'static str,
+    /// Message attached to each finding.
+    pub message: &'static str,
+    pub default_severity: Severity,
+    /// Whether the lint is run unless explicitly disabled.
+    pub enabled_by_default: bool,
+    /// Whether the lint needs the game pak to be passed to [`run_lints`], it is skipped otherwise.
+    pub requires_game_pak: bool,
+    run: fn(&LintCtxt) -> Result<Box<dyn LintOutput>, LintError>,
+}
+
+impl std::fmt::Debug for LintFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LintFactory")
+            .field("id", &self.id)
+            .field("default_severity", &self.default_severity)
+            .finish()
+    }
+}
+
+inventory::collect!(LintFactory);
+
+impl LintFactory {
+    /// All registered lints ordered by id.
+    pub fn all() -> Vec<&'static LintFactory> {
+        let mut factories = inventory::iter::<LintFactory>().collect::<Vec<_>>();
+        factories.sort_by_key(|f| f.id);
+        factories
+    }
+
+    pub fn get(id: LintId) -> Option<&'static LintFactory> {
+        inventory::iter::<LintFactory>().find(|f| f.id == id)
+    }
+}
+
+fn run_lint<L: Lint + Default>(lcx: &LintCtxt) -> Result<Box<dyn LintOutput>, LintError> {
+    Ok(Box::new(L::default().check_mods(lcx)?))
+}
+
+/// Stable identifier of a lint, serialized as its name. Every lint declares its id as an associated
+/// constant in its own module, next to the [`LintFactory`] registering it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
 pub struct LintId {
     name: &'static str,
 }
 
 impl LintId {
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
     pub fn to_name_lower(&self) -> String {
         self.name.to_ascii_lowercase()
     }
 
-    pub const CONFLICTING: Self = LintId {
-        name: "conflicting",
-    };
-    pub const ASSET_REGISTRY_BIN: Self = LintId {
-        name: "asset_registry_bin",
-    };
-    pub const SHADER_FILES: Self = LintId {
-        name: "shader_files",
-    };
-    pub const OUTDATED_PAK_VERSION: Self = LintId {
-        name: "outdated_pak_version",
-    };
-    pub const EMPTY_ARCHIVE: Self = LintId {
-        name: "empty_archive",
-    };
-    pub const ARCHIVE_WITH_ONLY_NON_PAK_FILES: Self = LintId {
-        name: "archive_only_non_pak_files",
-    };
-    pub const ARCHIVE_WITH_MULTIPLE_PAKS: Self = LintId {
-        name: "archive_with_multiple_paks",
-    };
-    pub const NON_ASSET_FILES: Self = LintId {
-        name: "non_asset_files",
-    };
-    pub const SPLIT_ASSET_PAIRS: Self = LintId {
-        name: "split_asset_pairs",
-    };
-    pub const UNMODIFIED_GAME_ASSETS: Self = LintId {
-        name: "unmodified_game_assets",
-    };
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// All registered lints.
+    pub fn all() -> BTreeSet<Self> {
+        inventory::iter::<LintFactory>().map(|f| f.id).collect()
+    }
+
+    /// Lints run unless explicitly disabled.
+    pub fn defaults() -> BTreeSet<Self> {
+        inventory::iter::<LintFactory>()
+            .filter(|f| f.enabled_by_default)
+            .map(|f| f.id)
+            .collect()
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        inventory::iter::<LintFactory>()
+            .map(|f| f.id)
+            .find(|id| id.name == name)
+    }
+}
+
+impl std::str::FromStr for LintId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| {
+            format!(
+                "unknown lint `{s}`, expected one of: {}",
+                Self::all()
+                    .into_iter()
+                    .map(|id| id.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+    }
+}
+
+/// Hides the findings of a lint for a mod, e.g. known-acceptable shader files.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintSuppression {
+    /// Name of the lint. Kept as a string so suppressions of removed lints don't prevent loading
+    /// the profile.
+    pub lint: String,
+    #[serde(rename = "mod")]
+    pub spec: ModSpecification,
+}
+
+impl LintSuppression {
+    pub fn new(lint: LintId, spec: ModSpecification) -> Self {
+        Self {
+            lint: lint.name.to_string(),
+            spec,
+        }
+    }
+
+    pub fn matches(&self, lint: LintId, spec: &ModSpecification) -> bool {
+        self.lint == lint.name && self.spec == *spec
+    }
 }
 
+/// Outputs of the lints that were run.
 #[derive(Default, Debug)]
 pub struct LintReport {
-    pub conflicting_mods: Option<BTreeMap<String, IndexSet<ModSpecification>>>,
-    pub asset_register_bin_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
-    pub shader_file_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
-    pub outdated_pak_version_mods: Option<BTreeMap<ModSpecification, repak::Version>>,
-    pub empty_archive_mods: Option<BTreeSet<ModSpecification>>,
-    pub archive_with_only_non_pak_files_mods: Option<BTreeSet<ModSpecification>>,
-    pub archive_with_multiple_paks_mods: Option<BTreeSet<ModSpecification>>,
-    pub non_asset_file_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
-    pub split_asset_pairs_mods:
-        Option<BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>>>,
-    pub unmodified_game_assets_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
+    outputs: BTreeMap<LintId, Box<dyn LintOutput>>,
+}
+
+impl LintReport {
+    /// Typed output of a lint, `None` if the lint was not run or `T` is not its output type.
+    pub fn get<T: LintOutput>(&self, id: LintId) -> Option<&T> {
+        self.outputs
+            .get(&id)
+            .and_then(|output| output.as_any().downcast_ref::<T>())
+    }
+
+    /// Lints that were run to produce this report.
+    pub fn lints(&self) -> BTreeSet<LintId> {
+        self.outputs.keys().copied().collect()
+    }
+
+    /// Outputs of the lints that were run, ordered by id.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static LintFactory, &dyn LintOutput)> {
+        self.outputs.iter().filter_map(|(id, output)| {
+            LintFactory::get(*id).map(|factory| (factory, output.as_ref()))
+        })
+    }
 }
 
 pub fn run_lints(
@@ -275,54 +394,48 @@ pub fn run_lints(
     mods: IndexSet<(ModSpecification, PathBuf)>,
     fsd_pak_path: Option<PathBuf>,
 ) -> Result<LintReport, LintError> {
-    let lint_ctxt = LintCtxt::init(mods, fsd_pak_path)?;
+    run_lints_with_conflict_rules(enabled_lints, mods, fsd_pak_path, ConflictRules::default())
+}
+
+/// Like [`run_lints`] but reports the mod winning each conflict according to `conflict_rules`.
+/// `mods` are in ascending order of precedence as passed to integration. Lints that require the
+/// game pak are skipped without `fsd_pak_path`.
+pub fn run_lints_with_conflict_rules(
+    enabled_lints: &BTreeSet<LintId>,
+    mods: IndexSet<(ModSpecification, PathBuf)>,
+    fsd_pak_path: Option<PathBuf>,
+    conflict_rules: ConflictRules,
+) -> Result<LintReport, LintError> {
+    let has_game_pak = fsd_pak_path.is_some();
+    let lint_ctxt = LintCtxt::init(mods, fsd_pak_path)?.with_conflict_rules(conflict_rules);
     let mut lint_report = LintReport::default();
 
     for lint_id in enabled_lints {
-        match *lint_id {
-            LintId::CONFLICTING => {
-                let res = ConflictingModsLint.check_mods(&lint_ctxt)?;
-                lint_report.conflicting_mods = Some(res);
-            }
-            LintId::ASSET_REGISTRY_BIN => {
-                let res = AssetRegisterBinLint.check_mods(&lint_ctxt)?;
-                lint_report.asset_register_bin_mods = Some(res);
-            }
-            LintId::SHADER_FILES => {
-                let res = ShaderFilesLint.check_mods(&lint_ctxt)?;
-                lint_report.shader_file_mods = Some(res);
-            }
-            LintId::OUTDATED_PAK_VERSION => {
-                let res = OutdatedPakVersionLint.check_mods(&lint_ctxt)?;
-                lint_report.outdated_pak_version_mods = Some(res);
-            }
-            LintId::EMPTY_ARCHIVE => {
-                let res = EmptyArchiveLint.check_mods(&lint_ctxt)?;
-                lint_report.empty_archive_mods = Some(res);
-            }
-            LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES => {
-                let res = ArchiveOnlyNonPakFilesLint.check_mods(&lint_ctxt)?;
-                lint_report.archive_with_only_non_pak_files_mods = Some(res);
-            }
-            LintId::ARCHIVE_WITH_MULTIPLE_PAKS => {
-                let res = ArchiveMultiplePaksLint.check_mods(&lint_ctxt)?;
-                lint_report.archive_with_multiple_paks_mods = Some(res);
-            }
-            LintId::NON_ASSET_FILES => {
-                let res = NonAssetFilesLint.check_mods(&lint_ctxt)?;
-                lint_report.non_asset_file_mods = Some(res);
-            }
-            LintId::SPLIT_ASSET_PAIRS => {
-                let res = SplitAssetPairsLint.check_mods(&lint_ctxt)?;
-                lint_report.split_asset_pairs_mods = Some(res);
-            }
-            LintId::UNMODIFIED_GAME_ASSETS => {
-                let res = UnmodifiedGameAssetsLint.check_mods(&lint_ctxt)?;
-                lint_report.unmodified_game_assets_mods = Some(res);
-            }
-            _ => unimplemented!(),
+        let factory =
+            LintFactory::get(*lint_id).context(UnknownLintSnafu { name: lint_id.name })?;
+        if factory.requires_game_pak && !has_game_pak {
+            warn!(
+                "skipping lint {}, it requires the game pak path",
+                lint_id.name
+            );
+            continue;
         }
+        trace!("running lint {}", lint_id.name);
+        let output = (factory.run)(&lint_ctxt)?;
+        lint_report.outputs.insert(*lint_id, output);
     }
 
     Ok(lint_report)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_skip_lints_requiring_game_pak() {
+        let lints = BTreeSet::from([LintId::ASSET_VERSIONS, LintId::CONFLICTING]);
+        let report = run_lints(&lints, IndexSet::new(), None).unwrap();
+        assert_eq!(report.lints(), BTreeSet::from([LintId::CONFLICTING]));
+    }
+}
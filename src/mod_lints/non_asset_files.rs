@@ -2,7 +2,23 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const NON_ASSET_FILES: Self = LintId::new("non_asset_files");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::NON_ASSET_FILES,
+        description: "Mods containing non-asset files",
+        message: "mod contains non-asset files",
+        default_severity: Severity::Info,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<NonAssetFilesLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct NonAssetFilesLint;
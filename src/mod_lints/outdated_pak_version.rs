@@ -1,8 +1,25 @@
+use std::any::Any;
 use std::collections::BTreeMap;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintFactory, LintFinding, LintId, LintOutput, Severity};
+
+impl LintId {
+    pub const OUTDATED_PAK_VERSION: Self = LintId::new("outdated_pak_version");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::OUTDATED_PAK_VERSION,
+        description: "Mods containing outdated pak version",
+        message: "pak version is outdated",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<OutdatedPakVersionLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct OutdatedPakVersionLint;
@@ -28,3 +45,34 @@ impl Lint for OutdatedPakVersionLint {
         Ok(outdated_pak_version_mods)
     }
 }
+
+impl LintOutput for BTreeMap<ModSpecification, repak::Version> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        self.iter()
+            .map(|(spec, version)| {
+                LintFinding::new(
+                    lint,
+                    vec![spec.clone()],
+                    format!("pak version {version:?} is outdated"),
+                )
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(
+            self.iter()
+                .map(|(spec, version)| (&spec.url, format!("{version:?}")))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec, _| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
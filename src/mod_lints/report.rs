@@ -0,0 +1,410 @@
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::providers::ModSpecification;
+
+use super::{LintFactory, LintId, LintOutput, LintReport, LintSuppression};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Info => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A single problem reported by a lint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub lint: LintId,
+    pub severity: Severity,
+    /// Mods the finding applies to.
+    pub mods: Vec<ModSpecification>,
+    /// Affected lowercase paths relative to the pak root, if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    pub message: String,
+}
+
+impl LintFinding {
+    /// Finding of `lint` with its default severity.
+    pub fn new(lint: &LintFactory, mods: Vec<ModSpecification>, message: String) -> Self {
+        Self {
+            lint: lint.id,
+            severity: lint.default_severity,
+            mods,
+            paths: vec![],
+            message,
+        }
+    }
+
+    pub fn with_paths<I: IntoIterator<Item = String>>(mut self, paths: I) -> Self {
+        self.paths = paths.into_iter().collect();
+        self
+    }
+}
+
+/// Mods and the offending paths they contain.
+impl LintOutput for BTreeMap<ModSpecification, BTreeSet<String>> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        self.iter()
+            .map(|(spec, paths)| {
+                LintFinding::new(lint, vec![spec.clone()], lint.message.to_string())
+                    .with_paths(paths.iter().cloned())
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(
+            self.iter()
+                .map(|(spec, paths)| (&spec.url, paths))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec, _| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl LintOutput for BTreeSet<ModSpecification> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        self.iter()
+            .map(|spec| LintFinding::new(lint, vec![spec.clone()], lint.message.to_string()))
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.iter().map(|spec| &spec.url).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LintFormat {
+    /// Human readable list of findings.
+    #[default]
+    Text,
+    Json,
+    /// SARIF 2.1.0, understood by most code scanning tools.
+    Sarif,
+    /// JUnit XML, one test case per lint and finding.
+    Junit,
+}
+
+impl LintReport {
+    /// Flatten the report into individual findings, ordered by lint.
+    pub fn findings(&self) -> Vec<LintFinding> {
+        self.iter()
+            .flat_map(|(lint, output)| output.findings(lint))
+            .collect()
+    }
+
+    /// Remove suppressed findings. Findings concerning several mods, such as conflicts, are
+    /// removed if any of the mods is suppressed.
+    pub fn suppress(&mut self, suppressions: &[LintSuppression]) {
+        if suppressions.is_empty() {
+            return;
+        }
+        for (lint, output) in &mut self.outputs {
+            output.retain_mods(&|spec| !suppressions.iter().any(|s| s.matches(*lint, spec)));
+        }
+    }
+
+    /// Render the report in `format`.
+    pub fn format(&self, format: LintFormat) -> String {
+        let findings = self.findings();
+        match format {
+            LintFormat::Text => format_text(&findings),
+            LintFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                "lints": self.lints(),
+                "findings": findings,
+                "outputs": self
+                    .iter()
+                    .map(|(lint, output)| (lint.id.name(), output.to_json()))
+                    .collect::<BTreeMap<_, _>>(),
+            }))
+            .unwrap(),
+            LintFormat::Sarif => format_sarif(&self.lints(), &findings),
+            LintFormat::Junit => format_junit(&self.lints(), &findings),
+        }
+    }
+}
+
+fn mod_urls(mods: &[ModSpecification]) -> String {
+    mods.iter()
+        .map(|m| m.url.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_text(findings: &[LintFinding]) -> String {
+    let mut out = String::new();
+    for finding in findings {
+        writeln!(
+            out,
+            "{}[{}] {}: {}",
+            finding.severity.as_str(),
+            finding.lint.name(),
+            mod_urls(&finding.mods),
+            finding.message
+        )
+        .unwrap();
+        for path in &finding.paths {
+            writeln!(out, "    {path}").unwrap();
+        }
+    }
+    if findings.is_empty() {
+        out.push_str("no problems found\n");
+    }
+    out
+}
+
+fn format_sarif(lints: &BTreeSet<LintId>, findings: &[LintFinding]) -> String {
+    use serde_json::json;
+
+    let rules = lints
+        .iter()
+        .map(|lint| {
+            json!({
+                "id": lint.name(),
+                "shortDescription": { "text": LintFactory::get(*lint).map(|f| f.description) },
+                "defaultConfiguration": {
+                    "level": LintFactory::get(*lint).map(|f| f.default_severity.sarif_level()),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = findings
+        .iter()
+        .map(|finding| {
+            json!({
+                "ruleId": finding.lint.name(),
+                "level": finding.severity.sarif_level(),
+                "message": {
+                    "text": format!("{}: {}", mod_urls(&finding.mods), finding.message),
+                },
+                "locations": finding
+                    .paths
+                    .iter()
+                    .map(|path| json!({
+                        "physicalLocation": { "artifactLocation": { "uri": path } },
+                    }))
+                    .collect::<Vec<_>>(),
+                "properties": {
+                    "mods": finding.mods.iter().map(|m| &m.url).collect::<Vec<_>>(),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "mint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    }))
+    .unwrap()
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_junit(lints: &BTreeSet<LintId>, findings: &[LintFinding]) -> String {
+    let failures = findings
+        .iter()
+        .filter(|f| f.severity > Severity::Info)
+        .count();
+    let tests = lints
+        .iter()
+        .map(|lint| findings.iter().filter(|f| f.lint == *lint).count().max(1))
+        .sum::<usize>();
+
+    let mut out = String::new();
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        out,
+        r#"<testsuites name="mint lint" tests="{tests}" failures="{failures}">"#
+    )
+    .unwrap();
+    for lint in lints {
+        let lint_findings = findings
+            .iter()
+            .filter(|f| f.lint == *lint)
+            .collect::<Vec<_>>();
+        let lint_failures = lint_findings
+            .iter()
+            .filter(|f| f.severity > Severity::Info)
+            .count();
+        writeln!(
+            out,
+            r#"  <testsuite name="{}" tests="{}" failures="{lint_failures}">"#,
+            lint.name(),
+            lint_findings.len().max(1),
+        )
+        .unwrap();
+        if lint_findings.is_empty() {
+            writeln!(
+                out,
+                r#"    <testcase classname="{0}" name="{0}"/>"#,
+                lint.name()
+            )
+            .unwrap();
+        }
+        for finding in lint_findings {
+            let name = xml_escape(&mod_urls(&finding.mods));
+            let details = xml_escape(&finding.paths.join("\n"));
+            writeln!(
+                out,
+                r#"    <testcase classname="{}" name="{name}">"#,
+                lint.name()
+            )
+            .unwrap();
+            if finding.severity > Severity::Info {
+                writeln!(
+                    out,
+                    r#"      <failure type="{}" message="{}">{details}</failure>"#,
+                    finding.severity.as_str(),
+                    xml_escape(&finding.message),
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    out,
+                    "      <system-out>{}\n{details}</system-out>",
+                    xml_escape(&finding.message)
+                )
+                .unwrap();
+            }
+            writeln!(out, "    </testcase>").unwrap();
+        }
+        writeln!(out, "  </testsuite>").unwrap();
+    }
+    writeln!(out, "</testsuites>").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn report() -> LintReport {
+        let mut report = LintReport::default();
+        report.outputs.insert(
+            LintId::EMPTY_ARCHIVE,
+            Box::new(BTreeSet::from([ModSpecification::new("a&b".into())])),
+        );
+        report.outputs.insert(
+            LintId::SHADER_FILES,
+            Box::new(BTreeMap::<ModSpecification, BTreeSet<String>>::new()),
+        );
+        report
+    }
+
+    #[test]
+    fn test_findings() {
+        let findings = report().findings();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].lint, LintId::EMPTY_ARCHIVE);
+        assert_eq!(findings[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_suppress() {
+        let mut report = report();
+        report.suppress(&[LintSuppression::new(
+            LintId::EMPTY_ARCHIVE,
+            ModSpecification::new("a&b".into()),
+        )]);
+        assert!(report.findings().is_empty());
+        assert_eq!(report.lints().len(), 2);
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&report().format(LintFormat::Json)).unwrap();
+        assert_eq!(
+            json["lints"],
+            serde_json::json!(["empty_archive", "shader_files"])
+        );
+        assert_eq!(json["findings"][0]["lint"], "empty_archive");
+        assert_eq!(json["findings"][0]["mods"][0]["url"], "a&b");
+        assert_eq!(json["outputs"]["empty_archive"], serde_json::json!(["a&b"]));
+    }
+
+    #[test]
+    fn test_junit() {
+        let xml = report().format(LintFormat::Junit);
+        assert!(xml.contains(r#"<testcase classname="shader_files" name="shader_files"/>"#));
+        assert!(xml.contains(r#"name="a&amp;b""#));
+        assert!(xml.contains(r#"failures="1""#));
+    }
+}
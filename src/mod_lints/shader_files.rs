@@ -2,7 +2,23 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const SHADER_FILES: Self = LintId::new("shader_files");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::SHADER_FILES,
+        description: "Mods containing shader files",
+        message: "mod contains shader files",
+        default_severity: Severity::Warning,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<ShaderFilesLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct ShaderFilesLint;
@@ -1,15 +1,34 @@
+use std::any::Any;
 use std::collections::{BTreeMap, BTreeSet};
 
+use serde::Serialize;
 use tracing::trace;
 
 use crate::providers::ModSpecification;
 
-use super::{Lint, LintCtxt, LintError};
+use super::{Lint, LintCtxt, LintError, LintFactory, LintFinding, LintId, LintOutput, Severity};
+
+impl LintId {
+    pub const SPLIT_ASSET_PAIRS: Self = LintId::new("split_asset_pairs");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::SPLIT_ASSET_PAIRS,
+        description: "Mods containing split {uexp, uasset} pairs",
+        message: "asset is missing its .uexp or .uasset",
+        default_severity: Severity::Error,
+        enabled_by_default: true,
+        requires_game_pak: false,
+        run: super::run_lint::<SplitAssetPairsLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct SplitAssetPairsLint;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SplitAssetPair {
     MissingUexp,
     MissingUasset,
@@ -98,3 +117,41 @@ impl Lint for SplitAssetPairsLint {
         Ok(split_asset_pairs_mods)
     }
 }
+
+impl LintOutput for BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>> {
+    fn findings(&self, lint: &LintFactory) -> Vec<LintFinding> {
+        self.iter()
+            .flat_map(|(spec, pairs)| {
+                pairs.iter().map(|(path, pair)| {
+                    LintFinding::new(
+                        lint,
+                        vec![spec.clone()],
+                        match pair {
+                            SplitAssetPair::MissingUexp => "asset is missing its .uexp",
+                            SplitAssetPair::MissingUasset => "asset is missing its .uasset",
+                        }
+                        .to_string(),
+                    )
+                    .with_paths([path.clone()])
+                })
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(
+            self.iter()
+                .map(|(spec, pairs)| (&spec.url, pairs))
+                .collect::<BTreeMap<_, _>>(),
+        )
+        .unwrap()
+    }
+
+    fn retain_mods(&mut self, keep: &dyn Fn(&ModSpecification) -> bool) {
+        self.retain(|spec, _| keep(spec));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
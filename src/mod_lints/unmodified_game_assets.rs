@@ -11,7 +11,24 @@ use tracing::trace;
 
 use crate::providers::ModSpecification;
 
-use super::{InvalidGamePathSnafu, Lint, LintCtxt, LintError};
+use super::{InvalidGamePathSnafu, Lint, LintCtxt, LintError, LintId, Severity};
+
+impl LintId {
+    pub const UNMODIFIED_GAME_ASSETS: Self = LintId::new("unmodified_game_assets");
+}
+
+inventory::submit! {
+    super::LintFactory {
+        id: LintId::UNMODIFIED_GAME_ASSETS,
+        description: "Mods containing unmodified game assets",
+        message: "mod contains unmodified game assets",
+        default_severity: Severity::Info,
+        // reads the entire game pak and is slow, so it has to be enabled explicitly
+        enabled_by_default: false,
+        requires_game_pak: true,
+        run: super::run_lint::<UnmodifiedGameAssetsLint>,
+    }
+}
 
 #[derive(Default)]
 pub struct UnmodifiedGameAssetsLint;
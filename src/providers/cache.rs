@@ -1,11 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 use fs_err as fs;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use snafu::prelude::*;
+use tracing::warn;
 
 use crate::state::config::ConfigWrapper;
 
@@ -186,6 +189,30 @@ pub(crate) fn read_cache_metadata_or_default(
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BlobRef(String);
 
+impl BlobRef {
+    /// Hex encoded sha256 the blob is named by.
+    pub fn hash(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Hex encoded sha256 of a file, the same hash blobs are named by.
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(
+        &mut std::io::BufReader::new(fs::File::open(path.as_ref())?),
+        &mut hasher,
+    )?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Prefix of the temporary files of [`BlobWriter`]s.
+const PARTIAL_PREFIX: &str = ".partial-";
+/// Extension of the file next to a partial blob holding its [`BlobWriter::validator`].
+const VALIDATOR_EXTENSION: &str = "validator";
+/// Age after which unfinished writes are considered abandoned.
+pub(super) const ABANDONED_AFTER: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
 #[derive(Debug, Snafu)]
 #[snafu(display("blob cache {kind} failed"))]
 pub struct BlobCacheError {
@@ -196,6 +223,10 @@ pub struct BlobCacheError {
 #[derive(Debug, Clone)]
 pub struct BlobCache {
     path: PathBuf,
+    /// Blobs whose content has been checked against their name since startup.
+    verified: Arc<Mutex<HashSet<String>>>,
+    /// Whether [`BlobCache::get_path`] hashes blobs it has not checked yet.
+    verify_on_access: bool,
 }
 
 impl BlobCache {
@@ -203,12 +234,21 @@ impl BlobCache {
         fs::create_dir(&path).ok();
         Self {
             path: path.as_ref().to_path_buf(),
+            verified: Default::default(),
+            verify_on_access: true,
         }
     }
 
-    pub(super) fn write(&self, blob: &[u8]) -> Result<BlobRef, BlobCacheError> {
-        use sha2::{Digest, Sha256};
+    /// Handle to the same cache that neither hashes nor evicts blobs when their path is
+    /// requested, for queries such as garbage collection which must not modify the cache.
+    pub(super) fn without_verification(&self) -> Self {
+        Self {
+            verify_on_access: false,
+            ..self.clone()
+        }
+    }
 
+    pub(super) fn write(&self, blob: &[u8]) -> Result<BlobRef, BlobCacheError> {
         let mut hasher = Sha256::new();
         hasher.update(blob);
         let hash = hex::encode(hasher.finalize());
@@ -216,12 +256,295 @@ impl BlobCache {
         let tmp = self.path.join(format!(".{hash}"));
         fs::write(&tmp, blob).context(BlobCacheSnafu { kind: "write" })?;
         fs::rename(tmp, self.path.join(&hash)).context(BlobCacheSnafu { kind: "rename" })?;
+        self.verified.lock().unwrap().insert(hash.clone());
 
         Ok(BlobRef(hash))
     }
 
+    /// Writer for a new blob that is streamed to disk. Data a previous writer for the same `key`
+    /// wrote before being interrupted is kept so downloads can be resumed, see
+    /// [`BlobWriter::written`] and [`BlobWriter::validator`].
+    pub(super) fn writer(&self, key: &str) -> Result<BlobWriter, BlobCacheError> {
+        let path = self.path.join(format!(
+            "{PARTIAL_PREFIX}{}",
+            hex::encode(Sha256::digest(key))
+        ));
+        let validator_path = path.with_extension(VALIDATOR_EXTENSION);
+
+        let mut hasher = Sha256::new();
+        let written = match fs::File::open(&path) {
+            Ok(mut partial) => std::io::copy(&mut partial, &mut hasher)
+                .context(BlobCacheSnafu { kind: "resume" })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => Err(e).context(BlobCacheSnafu { kind: "resume" })?,
+        };
+        let validator = if written > 0 {
+            fs::read_to_string(&validator_path).ok()
+        } else {
+            None
+        };
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .context(BlobCacheSnafu { kind: "open" })?;
+
+        Ok(BlobWriter {
+            cache: self.clone(),
+            path,
+            validator_path,
+            validator,
+            file,
+            hasher,
+            written,
+        })
+    }
+
+    /// Directory next to the blobs for provider data that are not blobs, such as git clones.
+    pub(super) fn scratch_dir(&self, name: &str) -> PathBuf {
+        let dir = self.path.with_file_name(name);
+        fs::create_dir_all(&dir).ok();
+        dir
+    }
+
+    /// Path of the blob if it exists and is intact. A blob is hashed the first time it is
+    /// requested and evicted if it is corrupt so it gets fetched again, unless this handle was
+    /// created by [`BlobCache::without_verification`].
     pub(super) fn get_path(&self, blob: &BlobRef) -> Option<PathBuf> {
+        let path = self.get_path_unverified(blob)?;
+        if !self.verify_on_access || self.verified.lock().unwrap().contains(&blob.0) {
+            return Some(path);
+        }
+        match self.verify(&blob.0) {
+            Ok(true) => Some(path),
+            Ok(false) => {
+                warn!("cached blob {} is corrupt, evicting it", blob.0);
+                if let Err(e) = self.remove(&blob.0) {
+                    warn!("{e}");
+                }
+                None
+            }
+            Err(e) => {
+                warn!("{e}");
+                None
+            }
+        }
+    }
+
+    /// Path of the blob if it exists, without checking its content.
+    pub(super) fn get_path_unverified(&self, blob: &BlobRef) -> Option<PathBuf> {
         let path = self.path.join(&blob.0);
         path.exists().then_some(path)
     }
+
+    /// Hash the blob and check it against its name.
+    pub(super) fn verify(&self, name: &str) -> Result<bool, BlobCacheError> {
+        let hash = sha256_file(self.path.join(name)).context(BlobCacheSnafu { kind: "verify" })?;
+        let intact = hash == name;
+        if intact {
+            self.verified.lock().unwrap().insert(hash);
+        }
+        Ok(intact)
+    }
+
+    /// Names and sizes of all blobs. Unfinished writes are not included, see
+    /// [`BlobCache::list_abandoned`].
+    pub(super) fn list(&self) -> Result<Vec<(String, u64)>, BlobCacheError> {
+        let mut blobs = vec![];
+        for entry in fs::read_dir(&self.path).context(BlobCacheSnafu { kind: "list" })? {
+            let entry = entry.context(BlobCacheSnafu { kind: "list" })?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let metadata = entry.metadata().context(BlobCacheSnafu { kind: "list" })?;
+            if metadata.is_file() && !name.starts_with('.') {
+                blobs.push((name, metadata.len()));
+            }
+        }
+        Ok(blobs)
+    }
+
+    /// Names and sizes of unfinished writes that have not been touched for
+    /// [`ABANDONED_AFTER`], such as downloads that were never resumed. Newer ones may still be
+    /// in progress.
+    pub(super) fn list_abandoned(&self) -> Result<Vec<(String, u64)>, BlobCacheError> {
+        let mut abandoned = vec![];
+        for entry in fs::read_dir(&self.path).context(BlobCacheSnafu { kind: "list" })? {
+            let entry = entry.context(BlobCacheSnafu { kind: "list" })?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // removed together with their partial blob
+            if !name.starts_with('.') || name.ends_with(&format!(".{VALIDATOR_EXTENSION}")) {
+                continue;
+            }
+            let metadata = entry.metadata().context(BlobCacheSnafu { kind: "list" })?;
+            let age = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok());
+            if metadata.is_file() && age.is_some_and(|age| age >= ABANDONED_AFTER) {
+                abandoned.push((name, metadata.len()));
+            }
+        }
+        Ok(abandoned)
+    }
+
+    pub(super) fn remove(&self, name: &str) -> Result<(), BlobCacheError> {
+        self.verified.lock().unwrap().remove(name);
+        if name.starts_with(PARTIAL_PREFIX) {
+            let validator_path = self.path.join(name).with_extension(VALIDATOR_EXTENSION);
+            match fs::remove_file(validator_path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(e).context(BlobCacheSnafu { kind: "remove" })?
+                }
+                _ => {}
+            }
+        }
+        fs::remove_file(self.path.join(name)).context(BlobCacheSnafu { kind: "remove" })
+    }
+}
+
+/// Blob being written to a temporary file in the blob cache, hashed as it is written.
+pub struct BlobWriter {
+    cache: BlobCache,
+    path: PathBuf,
+    validator_path: PathBuf,
+    validator: Option<String>,
+    file: fs::File,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl BlobWriter {
+    /// Number of bytes written so far, including those resumed from an interrupted writer.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn write(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(buf)?;
+        self.hasher.update(buf);
+        self.written += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Hex encoded sha256 of the data written so far, the hash the blob gets named by once
+    /// finished.
+    pub fn hash(&self) -> String {
+        hex::encode(self.hasher.clone().finalize())
+    }
+
+    /// Validator (e.g. an ETag) of the resource the data written so far was taken from, as
+    /// passed to [`BlobWriter::set_validator`] by an interrupted writer.
+    pub fn validator(&self) -> Option<&str> {
+        self.validator.as_deref()
+    }
+
+    /// Remember the validator of the resource being written so an interrupted write is only
+    /// resumed while the resource is unchanged.
+    pub fn set_validator(&mut self, validator: Option<&str>) -> std::io::Result<()> {
+        match validator {
+            Some(validator) => fs::write(&self.validator_path, validator)?,
+            None => match fs::remove_file(&self.validator_path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            },
+        }
+        self.validator = validator.map(str::to_string);
+        Ok(())
+    }
+
+    /// Discard everything written so far, e.g. if a download cannot be resumed.
+    pub fn reset(&mut self) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        self.hasher = Sha256::new();
+        self.written = 0;
+        self.set_validator(None)
+    }
+
+    /// Delete the partial blob, e.g. if the downloaded data turned out to be invalid.
+    pub fn discard(self) {
+        drop(self.file);
+        for path in [&self.path, &self.validator_path] {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => warn!("{e}"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Move the complete blob into the cache.
+    pub fn finish(self) -> Result<BlobRef, BlobCacheError> {
+        self.file
+            .sync_all()
+            .context(BlobCacheSnafu { kind: "write" })?;
+        drop(self.file);
+
+        let hash = hex::encode(self.hasher.finalize());
+        fs::rename(&self.path, self.cache.path.join(&hash))
+            .context(BlobCacheSnafu { kind: "rename" })?;
+        if self.validator.is_some() {
+            fs::remove_file(&self.validator_path).ok();
+        }
+        self.cache.verified.lock().unwrap().insert(hash.clone());
+
+        Ok(BlobRef(hash))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_blob_writer_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(dir.path());
+
+        let mut writer = cache.writer("https://example.org/mod.pak").unwrap();
+        writer.write(b"hello ").unwrap();
+        writer.set_validator(Some("\"v1\"")).unwrap();
+        drop(writer);
+
+        let mut writer = cache.writer("https://example.org/mod.pak").unwrap();
+        assert_eq!(writer.written(), 6);
+        assert_eq!(writer.validator(), Some("\"v1\""));
+        writer.write(b"world").unwrap();
+        let blob = writer.finish().unwrap();
+
+        assert_eq!(blob.hash(), cache.write(b"hello world").unwrap().hash());
+        assert_eq!(
+            fs::read(cache.get_path(&blob).unwrap()).unwrap(),
+            b"hello world"
+        );
+        assert_eq!(cache.list().unwrap().len(), 1);
+
+        let mut writer = cache.writer("https://example.org/mod.pak").unwrap();
+        assert_eq!(writer.written(), 0);
+        assert_eq!(writer.validator(), None);
+        writer.write(b"stale").unwrap();
+        writer.set_validator(Some("\"v2\"")).unwrap();
+        writer.reset().unwrap();
+        assert_eq!(writer.validator(), None);
+        writer.write(b"fresh").unwrap();
+        let blob = writer.finish().unwrap();
+        assert_eq!(fs::read(cache.get_path(&blob).unwrap()).unwrap(), b"fresh");
+        // no validator files are left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_without_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let blob = BlobCache::new(dir.path()).write(b"intact").unwrap();
+        let path = dir.path().join(blob.hash());
+        fs::write(&path, b"corrupt").unwrap();
+
+        // a fresh handle has not checked the blob yet
+        let cache = BlobCache::new(dir.path());
+        assert_eq!(
+            cache.without_verification().get_path(&blob),
+            Some(path.clone())
+        );
+        assert!(path.exists());
+        assert_eq!(cache.get_path(&blob), None);
+        assert!(!path.exists());
+    }
 }
@@ -0,0 +1,384 @@
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::integrate::PathCaseNormalizer;
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: DIRECTORY_PROVIDER_ID,
+        new: DirectoryProvider::new_provider,
+        can_provide: |url| Path::new(url).is_dir(),
+        parameters: &[],
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirectoryProviderCache {
+    /// Paks built from directories, keyed by the hash of the directory's content.
+    paks: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for DirectoryProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Loose files in a directory laid out like the root of a pak, e.g. containing `FSD/Content/`,
+/// which are packed into a pak when fetched.
+#[derive(Debug)]
+pub struct DirectoryProvider {}
+
+impl DirectoryProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Pak previously built from the current content of `dir`, if any.
+    fn cached_pak(
+        &self,
+        hash: &str,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        cache
+            .read()
+            .unwrap()
+            .get::<DirectoryProviderCache>(DIRECTORY_PROVIDER_ID)
+            .and_then(|c| c.paks.get(hash))
+            .and_then(|r| blob_cache.get_path(r))
+    }
+
+    /// Build a pak from `dir` unless a pak of the same content is already cached.
+    fn pack(
+        &self,
+        dir: &Path,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Result<PathBuf, ProviderError> {
+        let files = list_files(dir).context(DirectoryIoSnafu { path: dir })?;
+        let hash = content_hash(&files).context(DirectoryIoSnafu { path: dir })?;
+
+        if let Some(path) = self.cached_pak(&hash, cache.clone(), blob_cache) {
+            return Ok(path);
+        }
+
+        info!("packing {} files in {}...", files.len(), dir.display());
+        let mut pak = tempfile::tempfile().context(DirectoryIoSnafu { path: dir })?;
+        write_pak(&mut pak, &files).context(PackDirectoryFailedSnafu { path: dir })?;
+
+        let mut writer = blob_cache.writer(&hash)?;
+        let mut copy = || -> std::io::Result<()> {
+            writer.reset()?;
+            pak.rewind()?;
+            let mut buf = vec![0; 1 << 16];
+            loop {
+                let read = pak.read(&mut buf)?;
+                if read == 0 {
+                    return Ok(());
+                }
+                writer.write(&buf[..read])?;
+            }
+        };
+        copy().context(DirectoryIoSnafu { path: dir })?;
+        let blob = writer.finish()?;
+        let path = blob_cache.get_path(&blob).unwrap();
+
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<DirectoryProviderCache>(DIRECTORY_PROVIDER_ID)
+            .paks
+            .insert(hash, blob);
+
+        Ok(path)
+    }
+}
+
+const DIRECTORY_PROVIDER_ID: &str = "directory";
+
+/// Files in `dir` and their paths relative to it with forward slashes, sorted by path. Hidden
+/// files and directories such as `.git` are skipped.
+pub(crate) fn list_files(dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    fn walk(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let path = format!("{prefix}{name}");
+            if entry.file_type()?.is_dir() {
+                walk(&entry.path(), &format!("{path}/"), files)?;
+            } else {
+                files.push((path, entry.path()));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = vec![];
+    walk(dir, "", &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Hash of the paths and contents of `files`.
+fn content_hash(files: &[(String, PathBuf)]) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    for (path, file) in files {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        let len = std::io::copy(
+            &mut std::io::BufReader::new(fs::File::open(file)?),
+            &mut hasher,
+        )?;
+        hasher.update(len.to_le_bytes());
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Write `files` to a pak with the same version, compression and mount point as the mod bundle.
+/// Like in the bundle, paths are case insensitive: directories differing only in case are merged
+/// using the case of the first one.
+fn write_pak<W: std::io::Write + Seek>(
+    writer: W,
+    files: &[(String, PathBuf)],
+) -> Result<(), repak::Error> {
+    let mut pak_writer = repak::PakBuilder::new()
+        .compression([repak::Compression::Zlib])
+        .writer(writer, repak::Version::V11, "../../../".to_string(), None);
+    let normalizer = PathCaseNormalizer::new(files.iter().map(|(path, _)| path));
+    let mut written = HashSet::new();
+    for (path, file) in files {
+        let path = normalizer.normalize(path);
+        if !written.insert(path.as_str().to_ascii_lowercase()) {
+            warn!(
+                "skipping {}, another file has the same path ignoring case",
+                file.display()
+            );
+            continue;
+        }
+        pak_writer.write_file(path.as_str(), &fs::read(file)?)?;
+    }
+    pak_writer.write_index()?;
+    Ok(())
+}
+
+fn mod_info(spec: &ModSpecification) -> ModInfo {
+    let name = Path::new(&spec.url)
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| spec.url.to_string());
+    ModInfo {
+        provider: DIRECTORY_PROVIDER_ID,
+        name: name.clone(),
+        spec: spec.clone(),
+        versions: vec![],
+        resolution: ModResolution::unresolvable(spec.url.clone().into(), name),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+        author: None,
+        version: super::file::version_from_name(&name),
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for DirectoryProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        _update: bool,
+        _cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        Ok(ModResponse::Resolve(mod_info(spec)))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        _update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        // walking, hashing and packing the directory is blocking file system work
+        let dir = PathBuf::from(&res.url.0);
+        let blob_cache = blob_cache.clone();
+        let path = tokio::task::spawn_blocking(move || Self::new().pack(&dir, cache, &blob_cache))
+            .await
+            .unwrap()?;
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        // only reports an existing pak, packing is left to fetching so queries such as garbage
+        // collection don't create blobs
+        let files = list_files(Path::new(&res.url.0)).ok()?;
+        let hash = content_hash(&files).ok()?;
+        self.cached_pak(&hash, cache, blob_cache)
+    }
+
+    fn cached_versions(
+        &self,
+        _spec: &ModSpecification,
+        _cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
+        Some(mod_info(spec))
+    }
+
+    fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        true
+    }
+
+    fn get_version_name(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
+        Some("latest".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::RwLock;
+
+    use super::*;
+    use crate::state::config::ConfigWrapper;
+
+    #[test]
+    fn test_pack() {
+        let dir = tempfile::tempdir().unwrap();
+        let mod_dir = dir.path().join("mod");
+        fs::create_dir_all(mod_dir.join("FSD/Content/Test")).unwrap();
+        fs::create_dir_all(mod_dir.join(".git")).unwrap();
+        fs::write(mod_dir.join("FSD/Content/Test/A.uasset"), b"asset").unwrap();
+        fs::write(mod_dir.join(".git/HEAD"), b"ref").unwrap();
+
+        let files = list_files(&mod_dir).unwrap();
+        assert_eq!(
+            files.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>(),
+            ["FSD/Content/Test/A.uasset"]
+        );
+
+        let cache: ProviderCache = Arc::new(RwLock::new(ConfigWrapper::memory(Default::default())));
+        let blob_cache = BlobCache::new(dir.path().join("blobs"));
+        let provider = DirectoryProvider::new();
+
+        let pak_path = provider.pack(&mod_dir, cache.clone(), &blob_cache).unwrap();
+        let mut reader = std::io::BufReader::new(fs::File::open(&pak_path).unwrap());
+        let pak = repak::PakBuilder::new().reader(&mut reader).unwrap();
+        assert_eq!(pak.mount_point(), "../../../");
+        assert_eq!(pak.files(), ["FSD/Content/Test/A.uasset"]);
+        assert_eq!(
+            pak.get("FSD/Content/Test/A.uasset", &mut reader).unwrap(),
+            b"asset"
+        );
+
+        // unchanged content is not packed again
+        assert_eq!(
+            provider.pack(&mod_dir, cache.clone(), &blob_cache).unwrap(),
+            pak_path
+        );
+
+        let res = mod_info(&ModSpecification::new(
+            mod_dir.to_string_lossy().to_string(),
+        ))
+        .resolution;
+        assert_eq!(
+            provider.fetch_cached(&res, cache.clone(), &blob_cache),
+            Some(pak_path.clone())
+        );
+
+        fs::write(mod_dir.join("FSD/Content/Test/A.uasset"), b"changed").unwrap();
+        // changed content is only packed when fetched
+        let blobs = blob_cache.list().unwrap().len();
+        assert_eq!(
+            provider.fetch_cached(&res, cache.clone(), &blob_cache),
+            None
+        );
+        assert_eq!(blob_cache.list().unwrap().len(), blobs);
+        assert_ne!(
+            provider.pack(&mod_dir, cache, &blob_cache).unwrap(),
+            pak_path
+        );
+    }
+
+    #[test]
+    fn test_write_pak_normalizes_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str, contents: &[u8]| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            path
+        };
+        // as listed from a case sensitive file system
+        let files = [
+            ("FSD/Content/Test/A.uasset".to_string(), file("a", b"asset")),
+            ("FSD/content/test/A.uexp".to_string(), file("b", b"exports")),
+            (
+                "fsd/content/test/a.uexp".to_string(),
+                file("c", b"duplicate"),
+            ),
+        ];
+
+        let mut buf = std::io::Cursor::new(vec![]);
+        write_pak(&mut buf, &files).unwrap();
+        buf.rewind().unwrap();
+        let pak = repak::PakBuilder::new().reader(&mut buf).unwrap();
+        assert_eq!(
+            pak.files(),
+            ["FSD/Content/Test/A.uasset", "FSD/Content/Test/A.uexp"]
+        );
+        assert_eq!(
+            pak.get("FSD/Content/Test/A.uexp", &mut buf).unwrap(),
+            b"exports"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use tokio::sync::mpsc::Sender;
 
@@ -13,11 +13,55 @@ inventory::submit! {
     super::ProviderFactory {
         id: FILE_PROVIDER_ID,
         new: FileProvider::new_provider,
-        can_provide: |url| Path::new(url).exists(),
+        can_provide: |url| Path::new(url).is_file(),
         parameters: &[],
     }
 }
 
+static RE_VERSION: OnceLock<regex::Regex> = OnceLock::new();
+
+/// Version at the end of a mod's file name without extension, e.g. `1.2` for `MyMod-v1.2` or
+/// `my_mod 1.2`.
+pub(crate) fn version_from_name(stem: &str) -> Option<String> {
+    let captures = RE_VERSION
+        .get_or_init(|| {
+            regex::Regex::new(r"(?:^|[-_ ])(?:[vV](\d+(?:\.\d+)*)|(\d+(?:\.\d+)+))$").unwrap()
+        })
+        .captures(stem)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(2))
+        .map(|m| m.as_str().to_string())
+}
+
+fn mod_info(spec: &ModSpecification) -> ModInfo {
+    let path = Path::new(&spec.url);
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| spec.url.to_string());
+    ModInfo {
+        provider: FILE_PROVIDER_ID,
+        name,
+        spec: spec.clone(),
+        versions: vec![],
+        resolution: ModResolution::unresolvable(
+            spec.url.clone().into(),
+            path.file_name()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+        author: None,
+        version: path
+            .file_stem()
+            .and_then(|s| version_from_name(&s.to_string_lossy())),
+    }
+}
+
 #[derive(Debug)]
 pub struct FileProvider {}
 
@@ -43,27 +87,7 @@ impl ModProvider for FileProvider {
         _update: bool,
         _cache: ProviderCache,
     ) -> Result<ModResponse, ProviderError> {
-        let path = Path::new(&spec.url);
-        let name = path
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| spec.url.to_string());
-        Ok(ModResponse::Resolve(ModInfo {
-            provider: FILE_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::unresolvable(
-                spec.url.clone().into(),
-                path.file_name()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-            ),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        }))
+        Ok(ModResponse::Resolve(mod_info(spec)))
     }
 
     async fn fetch_mod(
@@ -92,28 +116,26 @@ impl ModProvider for FileProvider {
         Ok(())
     }
 
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        _cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        Some(PathBuf::from(&res.url.0))
+    }
+
+    fn cached_versions(
+        &self,
+        _spec: &ModSpecification,
+        _cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        vec![]
+    }
+
     fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
-        let path = Path::new(&spec.url);
-        let name = path
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| spec.url.to_string());
-        Some(ModInfo {
-            provider: FILE_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::unresolvable(
-                spec.url.clone().into(),
-                path.file_name()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-            ),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        })
+        Some(mod_info(spec))
     }
 
     fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
@@ -124,3 +146,17 @@ impl ModProvider for FileProvider {
         Some("latest".to_string())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_version_from_name() {
+        assert_eq!(version_from_name("MyMod-v1.2").as_deref(), Some("1.2"));
+        assert_eq!(version_from_name("my_mod 1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(version_from_name("mod_V3").as_deref(), Some("3"));
+        assert_eq!(version_from_name("Mod2"), None);
+        assert_eq!(version_from_name("mod-3"), None);
+    }
+}
@@ -0,0 +1,535 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: GIT_PROVIDER_ID,
+        new: GitProvider::new_provider,
+        can_provide: |url| url.starts_with("git+"),
+        parameters: &[],
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitProviderCache {
+    /// Commits unpinned specifications resolved to when they were last updated.
+    commits: HashMap<String, String>,
+    /// Blobs of pinned resolutions.
+    blobs: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for GitProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct GitProvider {
+    /// Held while cloning or fetching into a repository directory as mods are fetched
+    /// concurrently and several may come from the same repository.
+    repo_locks: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl GitProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {
+            repo_locks: Default::default(),
+        }
+    }
+
+    fn repo_lock(&self, repo_dir: &Path) -> Arc<tokio::sync::Mutex<()>> {
+        self.repo_locks
+            .lock()
+            .unwrap()
+            .entry(repo_dir.to_path_buf())
+            .or_default()
+            .clone()
+    }
+}
+
+const GIT_PROVIDER_ID: &str = "git";
+
+/// Length commits are abbreviated to when shown as a version.
+const SHORT_COMMIT_LEN: usize = 7;
+
+fn short_commit(commit: &str) -> &str {
+    &commit[..commit.len().min(SHORT_COMMIT_LEN)]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GitRef {
+    Head,
+    Branch(String),
+    Tag(String),
+    Commit(String),
+}
+
+/// Pak in a git repository, e.g. `git+file:///path/repo#tag=v1.2&path=out/mod.pak`. The ref is
+/// one of `branch`, `tag` or `rev` (a full commit hash) and defaults to the remote's HEAD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GitUrl {
+    repo: String,
+    reference: GitRef,
+    path: String,
+}
+
+fn is_commit_hash(s: &str) -> bool {
+    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl GitUrl {
+    fn parse(url: &str) -> Option<Self> {
+        let (repo, fragment) = url.strip_prefix("git+")?.split_once('#')?;
+        // git would take these for options, e.g. `--upload-pack=<command>`
+        if repo.starts_with('-') {
+            return None;
+        }
+        let mut reference = GitRef::Head;
+        let mut path = None;
+        for (key, value) in url::form_urlencoded::parse(fragment.as_bytes()) {
+            match key.as_ref() {
+                "branch" | "tag" if value.starts_with('-') => return None,
+                "branch" => reference = GitRef::Branch(value.into_owned()),
+                "tag" => reference = GitRef::Tag(value.into_owned()),
+                "rev" if is_commit_hash(&value) => {
+                    reference = GitRef::Commit(value.to_ascii_lowercase())
+                }
+                "path" => path = Some(value.trim_start_matches('/').to_string()),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            repo: repo.to_string(),
+            reference,
+            path: path.filter(|p| !p.is_empty())?,
+        })
+    }
+
+    /// URL of the pak at `commit`.
+    fn pinned(&self, commit: &str) -> String {
+        let fragment = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("rev", commit)
+            .append_pair("path", &self.path)
+            .finish();
+        format!("git+{}#{fragment}", self.repo)
+    }
+
+    /// Ref to look up with `git ls-remote`, or `None` if already pinned to a commit.
+    fn remote_ref(&self) -> Option<String> {
+        match &self.reference {
+            GitRef::Head => Some("HEAD".to_string()),
+            GitRef::Branch(branch) => Some(format!("refs/heads/{branch}")),
+            GitRef::Tag(tag) => Some(format!("refs/tags/{tag}")),
+            GitRef::Commit(_) => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    fn mod_info(&self, spec: &ModSpecification, commit: &str) -> ModInfo {
+        ModInfo {
+            provider: GIT_PROVIDER_ID,
+            name: self.name(),
+            spec: spec.clone(),
+            versions: vec![],
+            resolution: ModResolution::resolvable(self.pinned(commit).into()),
+            suggested_require: false,
+            suggested_dependencies: vec![],
+            modio_tags: None,
+            modio_id: None,
+            author: None,
+            version: Some(match &self.reference {
+                GitRef::Tag(tag) => tag.clone(),
+                _ => short_commit(commit).to_string(),
+            }),
+        }
+    }
+}
+
+/// Commit `remote_ref` points to in the output of `git ls-remote`, preferring the commit an
+/// annotated tag points to over the tag object itself.
+fn parse_ls_remote(output: &str, remote_ref: &str) -> Option<String> {
+    let peeled = format!("{remote_ref}^{{}}");
+    let mut commit = None;
+    for (hash, name) in output.lines().filter_map(|l| l.split_once('\t')) {
+        if name == peeled {
+            return Some(hash.to_string());
+        } else if name == remote_ref {
+            commit = Some(hash.to_string());
+        }
+    }
+    commit
+}
+
+async fn git<I, S>(url: &str, args: I) -> Result<Output, ProviderError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    Command::new("git")
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        // specs may come from shared profiles, only allow the protocols git considers safe
+        .env("GIT_PROTOCOL_FROM_USER", "0")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| ProviderError::GitFailed {
+            url: url.to_string(),
+            message: format!("failed to run git: {e}"),
+        })
+}
+
+/// Run git and fail if it does not exit successfully.
+async fn git_checked<I, S>(url: &str, args: I) -> Result<Vec<u8>, ProviderError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = git(url, args).await?;
+    ensure!(
+        output.status.success(),
+        GitFailedSnafu {
+            url,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+    );
+    Ok(output.stdout)
+}
+
+#[async_trait::async_trait]
+impl ModProvider for GitProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        let git_url = GitUrl::parse(&spec.url).context(InvalidUrlSnafu {
+            url: spec.url.to_string(),
+        })?;
+        let Some(remote_ref) = git_url.remote_ref() else {
+            return Ok(ModResponse::Resolve(
+                self.get_mod_info(spec, cache).unwrap(),
+            ));
+        };
+        if !update && let Some(info) = self.get_mod_info(spec, cache.clone()) {
+            return Ok(ModResponse::Resolve(info));
+        }
+
+        info!("resolving {remote_ref} of {}...", git_url.repo);
+        let output = git_checked(
+            &spec.url,
+            [
+                "ls-remote",
+                "--",
+                &git_url.repo,
+                &remote_ref,
+                &format!("{remote_ref}^{{}}"),
+            ],
+        )
+        .await?;
+        let commit = parse_ls_remote(&String::from_utf8_lossy(&output), &remote_ref).context(
+            GitFailedSnafu {
+                url: &spec.url,
+                message: format!("{remote_ref} not found"),
+            },
+        )?;
+
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<GitProviderCache>(GIT_PROVIDER_ID)
+            .commits
+            .insert(spec.url.clone(), commit.clone());
+
+        Ok(ModResponse::Resolve(git_url.mod_info(spec, &commit)))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let url = &res.url.0;
+        let git_url = GitUrl::parse(url).context(InvalidUrlSnafu { url })?;
+        let GitRef::Commit(commit) = &git_url.reference else {
+            return InvalidUrlSnafu { url }.fail();
+        };
+
+        let path = if let Some(path) = if update {
+            None
+        } else {
+            self.fetch_cached(res, cache.clone(), blob_cache)
+        } {
+            path
+        } else {
+            let scratch_dir = blob_cache.scratch_dir("git");
+            let repo_dir = scratch_dir.join(hex::encode(Sha256::digest(&git_url.repo)));
+            let repo_lock = self.repo_lock(&repo_dir);
+            let _guard = repo_lock.lock().await;
+            if !repo_dir.exists() {
+                info!("cloning {}...", git_url.repo);
+                // cloned beside and moved in place so an interrupted clone is not mistaken for
+                // a complete one
+                let clone_dir =
+                    tempfile::tempdir_in(&scratch_dir).with_context(|_| BufferIoSnafu { url })?;
+                git_checked(
+                    url,
+                    [
+                        OsStr::new("clone"),
+                        OsStr::new("--mirror"),
+                        OsStr::new("--quiet"),
+                        OsStr::new("--"),
+                        OsStr::new(&git_url.repo),
+                        clone_dir.path().as_os_str(),
+                    ],
+                )
+                .await?;
+                fs_err::rename(clone_dir.into_path(), &repo_dir)
+                    .with_context(|_| BufferIoSnafu { url })?;
+            }
+            let has_commit = git(
+                url,
+                [
+                    OsStr::new("-C"),
+                    repo_dir.as_os_str(),
+                    OsStr::new("cat-file"),
+                    OsStr::new("-e"),
+                    OsStr::new(&format!("{commit}^{{commit}}")),
+                ],
+            )
+            .await?
+            .status
+            .success();
+            if !has_commit {
+                info!("fetching {}...", git_url.repo);
+                git_checked(
+                    url,
+                    [
+                        OsStr::new("-C"),
+                        repo_dir.as_os_str(),
+                        OsStr::new("fetch"),
+                        OsStr::new("--quiet"),
+                        OsStr::new("--prune"),
+                        OsStr::new("--"),
+                        OsStr::new("origin"),
+                    ],
+                )
+                .await?;
+            }
+
+            info!("reading {} at {commit}...", git_url.path);
+            let mut child = Command::new("git")
+                .arg("-C")
+                .arg(&repo_dir)
+                .args(["cat-file", "blob", &format!("{commit}:{}", git_url.path)])
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|e| ProviderError::GitFailed {
+                    url: url.to_string(),
+                    message: format!("failed to run git: {e}"),
+                })?;
+
+            let mut writer = blob_cache.writer(url)?;
+            writer.reset().with_context(|_| BufferIoSnafu { url })?;
+            let mut stdout = child.stdout.take().unwrap();
+            let mut buf = vec![0; 1 << 16];
+            loop {
+                let read = stdout
+                    .read(&mut buf)
+                    .await
+                    .with_context(|_| BufferIoSnafu { url })?;
+                if read == 0 {
+                    break;
+                }
+                writer
+                    .write(&buf[..read])
+                    .with_context(|_| BufferIoSnafu { url })?;
+            }
+            let output = child
+                .wait_with_output()
+                .await
+                .with_context(|_| BufferIoSnafu { url })?;
+            if !output.status.success() {
+                writer.discard();
+                return GitFailedSnafu {
+                    url,
+                    message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                }
+                .fail();
+            }
+
+            let blob = writer.finish()?;
+            let path = blob_cache.get_path(&blob).unwrap();
+            cache
+                .write()
+                .unwrap()
+                .get_mut::<GitProviderCache>(GIT_PROVIDER_ID)
+                .blobs
+                .insert(url.to_owned(), blob);
+            path
+        };
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        git_checked("git", ["--version"]).await.map(|_| ())
+    }
+
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        cache
+            .read()
+            .unwrap()
+            .get::<GitProviderCache>(GIT_PROVIDER_ID)
+            .and_then(|c| c.blobs.get(&res.url.0))
+            .and_then(|r| blob_cache.get_path(r))
+    }
+
+    fn cached_versions(
+        &self,
+        spec: &ModSpecification,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        self.get_mod_info(spec, cache.clone())
+            .and_then(|info| self.fetch_cached(&info.resolution, cache, blob_cache))
+            .into_iter()
+            .collect()
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        let git_url = GitUrl::parse(&spec.url)?;
+        let commit = match &git_url.reference {
+            GitRef::Commit(commit) => commit.clone(),
+            _ => cache
+                .read()
+                .unwrap()
+                .get::<GitProviderCache>(GIT_PROVIDER_ID)?
+                .commits
+                .get(&spec.url)?
+                .clone(),
+        };
+        Some(git_url.mod_info(spec, &commit))
+    }
+
+    fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        GitUrl::parse(&spec.url).is_some_and(|u| matches!(u.reference, GitRef::Commit(_)))
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        let info = self.get_mod_info(spec, cache)?;
+        match GitUrl::parse(&info.resolution.url.0)?.reference {
+            GitRef::Commit(commit) => Some(short_commit(&commit).to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        let url = GitUrl::parse("git+file:///path/repo#tag=v1.2&path=out/mod.pak").unwrap();
+        assert_eq!(url.repo, "file:///path/repo");
+        assert_eq!(url.reference, GitRef::Tag("v1.2".to_string()));
+        assert_eq!(url.path, "out/mod.pak");
+        assert_eq!(url.remote_ref().as_deref(), Some("refs/tags/v1.2"));
+        assert_eq!(url.name(), "mod.pak");
+
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        let pinned = GitUrl::parse(&url.pinned(commit)).unwrap();
+        assert_eq!(pinned.reference, GitRef::Commit(commit.to_string()));
+        assert_eq!(pinned.path, url.path);
+        assert_eq!(pinned.remote_ref(), None);
+
+        let head = GitUrl::parse("git+https://example.org/repo.git#path=mod.pak").unwrap();
+        assert_eq!(head.reference, GitRef::Head);
+
+        assert_eq!(GitUrl::parse("git+file:///path/repo#tag=v1.2"), None);
+        assert_eq!(GitUrl::parse("git+file:///path/repo#rev=abc&path=a"), None);
+        assert_eq!(GitUrl::parse("git+file:///path/repo"), None);
+    }
+
+    #[test]
+    fn test_parse_url_rejects_options() {
+        assert_eq!(GitUrl::parse("git+--upload-pack=touch pwned#path=a"), None);
+        assert_eq!(
+            GitUrl::parse("git+file:///path/repo#branch=--upload-pack=touch%20pwned&path=a"),
+            None
+        );
+        assert_eq!(GitUrl::parse("git+file:///path/repo#tag=-c&path=a"), None);
+    }
+
+    #[test]
+    fn test_parse_ls_remote() {
+        let output = "\
+            1111111111111111111111111111111111111111\trefs/tags/v1.2\n\
+            2222222222222222222222222222222222222222\trefs/tags/v1.2^{}\n\
+            3333333333333333333333333333333333333333\trefs/heads/main\n";
+        assert_eq!(
+            parse_ls_remote(output, "refs/tags/v1.2").as_deref(),
+            Some("2222222222222222222222222222222222222222")
+        );
+        assert_eq!(
+            parse_ls_remote(output, "refs/heads/main").as_deref(),
+            Some("3333333333333333333333333333333333333333")
+        );
+        assert_eq!(parse_ls_remote(output, "refs/heads/dev"), None);
+    }
+}
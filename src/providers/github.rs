@@ -0,0 +1,738 @@
+use std::sync::OnceLock;
+
+use mint_lib::update::GITHUB_REQ_USER_AGENT;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: GITHUB_PROVIDER_ID,
+        new: GitHubProvider::new_provider,
+        can_provide: is_release_url,
+        parameters: &[],
+    }
+}
+
+const GITHUB_PROVIDER_ID: &str = "github";
+const GITHUB_API_URL: &str = "https://api.github.com";
+/// Largest page size the releases endpoint accepts.
+const RELEASES_PER_PAGE: usize = 100;
+
+static RE_RELEASES: OnceLock<regex::Regex> = OnceLock::new();
+fn re_releases() -> &'static regex::Regex {
+    RE_RELEASES.get_or_init(|| {
+        regex::Regex::new(
+            r"^https://github\.com/(?P<owner>[^/#@]+)/(?P<repo>[^/#@]+)/releases/?(?:@(?P<tag>[^#]+))?(?:#(?P<asset>.+))?$",
+        )
+        .unwrap()
+    })
+}
+
+/// Whether `url` points to the releases of a GitHub repository, e.g.
+/// `https://github.com/owner/repo/releases@v1.0#*.pak`.
+pub fn is_release_url(url: &str) -> bool {
+    re_releases().is_match(url)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubRelease {
+    tag_name: String,
+    draft: bool,
+    prerelease: bool,
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitHubProviderCache {
+    /// Releases of `owner/repo`, newest first.
+    releases: HashMap<String, Vec<GitHubRelease>>,
+    asset_blobs: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for GitHubProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Parsed GitHub releases URL. Without a tag the latest release is used, without an asset glob
+/// the first `.pak` or `.zip` asset of the release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReleasesUrl {
+    owner: String,
+    repo: String,
+    tag: Option<String>,
+    asset: Option<String>,
+}
+
+impl ReleasesUrl {
+    fn parse(url: &str) -> Option<Self> {
+        let captures = re_releases().captures(url)?;
+        Some(Self {
+            owner: captures["owner"].to_string(),
+            repo: captures["repo"].to_string(),
+            tag: captures.name("tag").map(|m| m.as_str().to_string()),
+            asset: captures.name("asset").map(|m| m.as_str().to_string()),
+        })
+    }
+
+    fn key(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    /// URL of the release `tag` with its assets narrowed down to those matching `asset`.
+    fn release_url(&self, tag: &str, asset: Option<&str>) -> String {
+        let mut url = format!(
+            "https://github.com/{}/{}/releases@{tag}",
+            self.owner, self.repo
+        );
+        if let Some(asset) = asset {
+            url.push('#');
+            url.push_str(asset);
+        }
+        url
+    }
+
+    fn pinned(&self, tag: &str) -> ModSpecification {
+        ModSpecification::new(self.release_url(tag, self.asset.as_deref()))
+    }
+
+    /// Resolution of `asset` of `release`. It stays a releases URL rather than the asset's
+    /// download URL so fetching it goes through this provider.
+    fn resolution(&self, release: &GitHubRelease, asset: &GitHubAsset) -> ModResolution {
+        ModResolution::resolvable(
+            self.release_url(&release.tag_name, Some(&asset.name))
+                .into(),
+        )
+    }
+
+    fn select_release<'a>(&self, releases: &'a [GitHubRelease]) -> Option<&'a GitHubRelease> {
+        let mut published = releases.iter().filter(|r| !r.draft);
+        if let Some(tag) = &self.tag {
+            published.find(|r| &r.tag_name == tag)
+        } else {
+            published
+                .clone()
+                .find(|r| !r.prerelease)
+                .or_else(|| published.next())
+        }
+    }
+
+    fn select_asset<'a>(&self, release: &'a GitHubRelease) -> Option<&'a GitHubAsset> {
+        release.assets.iter().find(|a| match &self.asset {
+            Some(glob) => glob_match(glob, &a.name),
+            None => {
+                let name = a.name.to_ascii_lowercase();
+                name.ends_with(".pak") || name.ends_with(".zip")
+            }
+        })
+    }
+
+    fn find_asset<'a>(
+        &self,
+        url: &str,
+        releases: &'a [GitHubRelease],
+    ) -> Result<(&'a GitHubRelease, &'a GitHubAsset), ProviderError> {
+        let release = self
+            .select_release(releases)
+            .context(NoSuchReleaseSnafu { url })?;
+        let asset = self
+            .select_asset(release)
+            .context(NoMatchingReleaseAssetSnafu {
+                url,
+                tag: release.tag_name.to_string(),
+            })?;
+        Ok((release, asset))
+    }
+
+    fn mod_info(
+        &self,
+        spec: &ModSpecification,
+        releases: &[GitHubRelease],
+    ) -> Result<ModInfo, ProviderError> {
+        let (release, asset) = self.find_asset(&spec.url, releases)?;
+        Ok(ModInfo {
+            provider: GITHUB_PROVIDER_ID,
+            name: self.repo.clone(),
+            spec: spec.clone(),
+            versions: releases
+                .iter()
+                .filter(|r| !r.draft)
+                .map(|r| self.pinned(&r.tag_name))
+                .collect(),
+            resolution: self.resolution(release, asset),
+            suggested_require: false,
+            suggested_dependencies: vec![],
+            modio_tags: None,
+            modio_id: None,
+            author: Some(self.owner.clone()),
+            version: Some(release.tag_name.clone()),
+        })
+    }
+}
+
+/// Case insensitive match of `name` against a glob `pattern` supporting `*` and `?`.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let name = name.to_lowercase().chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` and the name position it is currently matched up to
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => {
+                let Some((star_p, star_n)) = star else {
+                    return false;
+                };
+                star = Some((star_p, star_n + 1));
+                p = star_p + 1;
+                n = star_n + 1;
+            }
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[derive(Debug)]
+pub struct GitHubProvider {
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl GitHubProvider {
+    /// Optional parameters are `api_url` to use a different API server and `token` to
+    /// authenticate requests, raising GitHub's rate limit.
+    pub fn new_provider(
+        parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new(
+            parameters
+                .get("api_url")
+                .map(String::as_str)
+                .unwrap_or(GITHUB_API_URL),
+            parameters.get("token").cloned(),
+        )?))
+    }
+
+    pub fn new(api_url: &str, token: Option<String>) -> Result<Self, ProviderError> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .user_agent(GITHUB_REQ_USER_AGENT)
+                .build()
+                .context(RequestFailedSnafu { url: api_url })?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// All releases of `url`, requested a page at a time until a page comes back short.
+    async fn fetch_releases(&self, url: &ReleasesUrl) -> Result<Vec<GitHubRelease>, ProviderError> {
+        info!("fetching releases of {}...", url.key());
+        let mut releases = vec![];
+        for page in 1.. {
+            let api_url = format!(
+                "{}/repos/{}/{}/releases?per_page={RELEASES_PER_PAGE}&page={page}",
+                self.api_url, url.owner, url.repo
+            );
+            let mut request = self
+                .client
+                .get(&api_url)
+                .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            let page: Vec<GitHubRelease> = request
+                .send()
+                .await
+                .context(RequestFailedSnafu { url: &api_url })?
+                .error_for_status()
+                .context(ResponseSnafu { url: &api_url })?
+                .json()
+                .await
+                .context(ResponseSnafu { url: &api_url })?;
+            let last = page.len() < RELEASES_PER_PAGE;
+            releases.extend(page);
+            if last {
+                break;
+            }
+        }
+        Ok(releases)
+    }
+
+    fn cached_releases(&self, key: &str, cache: &ProviderCache) -> Option<Vec<GitHubRelease>> {
+        cache
+            .read()
+            .unwrap()
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .and_then(|c| c.releases.get(key))
+            .cloned()
+    }
+
+    /// Releases of `url` from the cache, fetched again on `update` or if the release `url`
+    /// selects is not cached.
+    async fn releases(
+        &self,
+        url: &ReleasesUrl,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<Vec<GitHubRelease>, ProviderError> {
+        match self.cached_releases(&url.key(), cache) {
+            // a pinned release missing from the cache may have been published since
+            Some(releases) if !update && url.select_release(&releases).is_some() => Ok(releases),
+            _ => {
+                let releases = self.fetch_releases(url).await?;
+                cache
+                    .write()
+                    .unwrap()
+                    .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                    .releases
+                    .insert(url.key(), releases.clone());
+                Ok(releases)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for GitHubProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        let url = ReleasesUrl::parse(&spec.url).context(InvalidUrlSnafu {
+            url: spec.url.to_string(),
+        })?;
+
+        let releases = self.releases(&url, update, &cache).await?;
+        Ok(ModResponse::Resolve(url.mod_info(spec, &releases)?))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let path = if let Some(path) = if update {
+            None
+        } else {
+            self.fetch_cached(res, cache.clone(), blob_cache)
+        } {
+            path
+        } else {
+            let releases_url = ReleasesUrl::parse(&res.url.0).context(InvalidUrlSnafu {
+                url: res.url.0.to_string(),
+            })?;
+            let releases = self.releases(&releases_url, false, &cache).await?;
+            let (_, asset) = releases_url.find_asset(&res.url.0, &releases)?;
+            let url = &asset.browser_download_url;
+
+            info!("downloading mod {url:?}...");
+            let mut request = self.client.get(url);
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            let response = request
+                .send()
+                .await
+                .context(RequestFailedSnafu { url })?
+                .error_for_status()
+                .context(ResponseSnafu { url })?;
+            let size = response.content_length();
+
+            let mut writer = blob_cache.writer(url)?;
+            // release assets are immutable but resuming is not worth following the redirect
+            writer.reset().with_context(|_| BufferIoSnafu { url })?;
+
+            write_download(
+                response.bytes_stream(),
+                &mut writer,
+                res,
+                size,
+                tx.as_ref(),
+                FetchSnafu { url },
+                BufferIoSnafu { url },
+            )
+            .await?;
+
+            let blob = writer.finish()?;
+            let path = blob_cache.get_path(&blob).unwrap();
+            cache
+                .write()
+                .unwrap()
+                .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                .asset_blobs
+                .insert(res.url.0.to_owned(), blob);
+            path
+        };
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, cache: ProviderCache) -> Result<(), ProviderError> {
+        let keys = cache
+            .read()
+            .unwrap()
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .map(|c| c.releases.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        for key in keys {
+            let (owner, repo) = key.split_once('/').unwrap();
+            let url = ReleasesUrl {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                tag: None,
+                asset: None,
+            };
+            let releases = self.fetch_releases(&url).await?;
+            cache
+                .write()
+                .unwrap()
+                .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                .releases
+                .insert(key, releases);
+        }
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        cache
+            .read()
+            .unwrap()
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .and_then(|c| c.asset_blobs.get(&res.url.0))
+            .and_then(|r| blob_cache.get_path(r))
+    }
+
+    fn cached_versions(
+        &self,
+        spec: &ModSpecification,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        let Some(url) = ReleasesUrl::parse(&spec.url) else {
+            return vec![];
+        };
+        let cache = cache.read().unwrap();
+        let Some(prov) = cache.get::<GitHubProviderCache>(GITHUB_PROVIDER_ID) else {
+            return vec![];
+        };
+        prov.releases
+            .get(&url.key())
+            .into_iter()
+            .flatten()
+            .filter_map(|r| Some(url.resolution(r, url.select_asset(r)?)))
+            .filter_map(|res| prov.asset_blobs.get(&res.url.0))
+            .filter_map(|r| blob_cache.get_path_unverified(r))
+            .collect()
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        let url = ReleasesUrl::parse(&spec.url)?;
+        let releases = self.cached_releases(&url.key(), &cache)?;
+        url.mod_info(spec, &releases).ok()
+    }
+
+    fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        ReleasesUrl::parse(&spec.url).is_some_and(|url| url.tag.is_some())
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        let url = ReleasesUrl::parse(&spec.url)?;
+        let releases = self.cached_releases(&url.key(), &cache)?;
+        url.select_release(&releases).map(|r| r.tag_name.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::RwLock;
+
+    use super::*;
+    use crate::state::config::ConfigWrapper;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            ReleasesUrl::parse("https://github.com/owner/repo/releases"),
+            Some(ReleasesUrl {
+                owner: "owner".into(),
+                repo: "repo".into(),
+                tag: None,
+                asset: None,
+            })
+        );
+        assert_eq!(
+            ReleasesUrl::parse("https://github.com/owner/repo/releases@v1.2#*-client.pak"),
+            Some(ReleasesUrl {
+                owner: "owner".into(),
+                repo: "repo".into(),
+                tag: Some("v1.2".into()),
+                asset: Some("*-client.pak".into()),
+            })
+        );
+        assert_eq!(ReleasesUrl::parse("https://github.com/owner/repo"), None);
+        assert!(glob_match("*-client.PAK", "mod-1.2-client.pak"));
+        assert!(glob_match("mod-?.zip", "mod-1.zip"));
+        assert!(!glob_match("*-client.pak", "mod-server.pak"));
+    }
+
+    #[tokio::test]
+    async fn test_releases() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let releases = serde_json::json!([
+            {
+                "tag_name": "v3",
+                "draft": false,
+                "prerelease": true,
+                "assets": [{ "name": "mod.pak", "browser_download_url": format!("{base}/v3/mod.pak") }],
+            },
+            {
+                "tag_name": "v2",
+                "draft": false,
+                "prerelease": false,
+                "assets": [
+                    { "name": "README.md", "browser_download_url": format!("{base}/v2/README.md") },
+                    { "name": "mod.pak", "browser_download_url": format!("{base}/v2/mod.pak") },
+                ],
+            },
+            {
+                "tag_name": "v1",
+                "draft": false,
+                "prerelease": false,
+                "assets": [{ "name": "mod.zip", "browser_download_url": format!("{base}/v1/mod.zip") }],
+            },
+        ]);
+        let server = serve(
+            listener,
+            [
+                (
+                    "/repos/owner/repo/releases".to_string(),
+                    serde_json::to_vec(&releases).unwrap(),
+                ),
+                ("/v2/mod.pak".to_string(), b"v2 pak".to_vec()),
+                ("/v1/mod.zip".to_string(), b"v1 zip".to_vec()),
+            ]
+            .into(),
+        )
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache: ProviderCache = Arc::new(RwLock::new(ConfigWrapper::memory(Default::default())));
+        let blob_cache = BlobCache::new(dir.path());
+        let provider = GitHubProvider::new(&base, None).unwrap();
+
+        let resolve = |url: &str| {
+            let spec = ModSpecification::new(url.to_string());
+            let cache = cache.clone();
+            let provider = &provider;
+            async move {
+                match provider.resolve_mod(&spec, false, cache).await? {
+                    ModResponse::Resolve(info) => Ok::<_, ProviderError>(info),
+                    ModResponse::Redirect(_) => unreachable!(),
+                }
+            }
+        };
+
+        // latest non-prerelease
+        let info = resolve("https://github.com/owner/repo/releases")
+            .await
+            .unwrap();
+        assert_eq!(info.name, "repo");
+        assert_eq!(
+            info.resolution.url.0,
+            "https://github.com/owner/repo/releases@v2#mod.pak"
+        );
+        assert_eq!(
+            info.versions
+                .iter()
+                .map(|s| s.url.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "https://github.com/owner/repo/releases@v3",
+                "https://github.com/owner/repo/releases@v2",
+                "https://github.com/owner/repo/releases@v1",
+            ]
+        );
+
+        let spec = ModSpecification::new("https://github.com/owner/repo/releases@v1".into());
+        assert!(provider.is_pinned(&spec, cache.clone()));
+        assert_eq!(
+            provider.get_version_name(&spec, cache.clone()).as_deref(),
+            Some("v1")
+        );
+        let info = resolve(&spec.url).await.unwrap();
+        let path = provider
+            .fetch_mod(&info.resolution, false, cache.clone(), &blob_cache, None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"v1 zip");
+        assert_eq!(
+            provider.fetch_cached(&info.resolution, cache.clone(), &blob_cache),
+            Some(path)
+        );
+
+        assert!(matches!(
+            resolve("https://github.com/owner/repo/releases@v2#*.zip").await,
+            Err(ProviderError::NoMatchingReleaseAsset { .. })
+        ));
+        assert!(matches!(
+            resolve("https://github.com/owner/repo/releases@v4").await,
+            Err(ProviderError::NoSuchRelease { .. })
+        ));
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_releases_pages() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let release = |tag: &str| {
+            serde_json::json!({
+                "tag_name": tag,
+                "draft": false,
+                "prerelease": false,
+                "assets": [{ "name": "mod.pak", "browser_download_url": format!("{base}/{tag}/mod.pak") }],
+            })
+        };
+        let first = (0..RELEASES_PER_PAGE)
+            .map(|i| release(&format!("v{}", RELEASES_PER_PAGE - i)))
+            .collect::<Vec<_>>();
+        let second = vec![release("v0")];
+        let server = serve(
+            listener,
+            [
+                (
+                    format!("/repos/owner/repo/releases?per_page={RELEASES_PER_PAGE}&page=1"),
+                    serde_json::to_vec(&first).unwrap(),
+                ),
+                (
+                    format!("/repos/owner/repo/releases?per_page={RELEASES_PER_PAGE}&page=2"),
+                    serde_json::to_vec(&second).unwrap(),
+                ),
+            ]
+            .into(),
+        )
+        .await;
+
+        let cache: ProviderCache = Arc::new(RwLock::new(ConfigWrapper::memory(Default::default())));
+        let provider = GitHubProvider::new(&base, None).unwrap();
+        let spec = ModSpecification::new("https://github.com/owner/repo/releases@v0".into());
+        let ModResponse::Resolve(info) = provider.resolve_mod(&spec, false, cache).await.unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            info.resolution.url.0,
+            "https://github.com/owner/repo/releases@v0#mod.pak"
+        );
+        assert_eq!(info.versions.len(), RELEASES_PER_PAGE + 1);
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_through_store() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let releases = serde_json::json!([{
+            "tag_name": "v1",
+            "draft": false,
+            "prerelease": false,
+            "assets": [{ "name": "mod.pak", "browser_download_url": format!("{base}/v1/mod.pak") }],
+        }]);
+        let server = serve(
+            listener,
+            [
+                (
+                    "/repos/owner/repo/releases".to_string(),
+                    serde_json::to_vec(&releases).unwrap(),
+                ),
+                ("/v1/mod.pak".to_string(), b"v1 pak".to_vec()),
+            ]
+            .into(),
+        )
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let parameters = HashMap::from([(
+            GITHUB_PROVIDER_ID.to_string(),
+            HashMap::from([("api_url".to_string(), base.clone())]),
+        )]);
+        let store = ModStore::new(dir.path(), &parameters).unwrap();
+        let spec = ModSpecification::new("https://github.com/owner/repo/releases".into());
+
+        let (_, info) = store.resolve_mod(spec.clone(), false).await.unwrap();
+        let path = store
+            .fetch_mod(&info.resolution, false, None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"v1 pak");
+
+        // the download was recorded by this provider so it is found offline and kept by gc
+        store.set_offline(true);
+        assert_eq!(
+            store
+                .fetch_mod(&info.resolution, false, None)
+                .await
+                .unwrap(),
+            path
+        );
+        let stats = store
+            .gc_blobs(&[spec], &Default::default(), 0, false)
+            .unwrap();
+        assert_eq!((stats.kept, stats.removed), (1, 0));
+        assert!(path.exists());
+
+        server.abort();
+    }
+}
@@ -1,7 +1,8 @@
+use std::path::Path;
 use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::providers::*;
 
@@ -14,6 +15,7 @@ inventory::submit! {
                 .captures(url)
                 .and_then(|c| c.name("hostname"))
                 .is_some_and(|h| !["mod.io", "drg.mod.io", "drg.old.mod.io"].contains(&h.as_str()))
+                && !super::github::is_release_url(url)
         },
         parameters: &[],
     }
@@ -22,6 +24,9 @@ inventory::submit! {
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HttpProviderCache {
     url_blobs: HashMap<String, BlobRef>,
+    /// Versions found in the file name of the `Content-Disposition` header of downloads.
+    #[serde(default)]
+    url_versions: HashMap<String, String>,
 }
 
 #[typetag::serde]
@@ -65,37 +70,82 @@ fn re_mod() -> &'static regex::Regex {
 
 const HTTP_PROVIDER_ID: &str = "http";
 
+/// File name of a `Content-Disposition` header value such as `attachment; filename="mod.pak"`.
+fn content_disposition_file_name(value: &str) -> Option<&str> {
+    value
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix("filename="))
+        .map(|name| name.trim_matches('"'))
+}
+
+/// Strong ETag or Last-Modified date of a response, usable as `If-Range` to resume downloading it.
+fn resume_validator(headers: &reqwest::header::HeaderMap) -> Option<&str> {
+    let header =
+        |name: reqwest::header::HeaderName| headers.get(name).and_then(|v| v.to_str().ok());
+    header(reqwest::header::ETAG)
+        // weak ETags are not allowed in If-Range
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+}
+
+/// First byte position of a `Content-Range` header such as `bytes 100-199/200`.
+fn content_range_start(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn mod_info(spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+    let url = url::Url::parse(&spec.url).ok()?;
+    let name = url
+        .path_segments()
+        .and_then(|s| s.last())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| url.to_string());
+    let version = Path::new(&name)
+        .file_stem()
+        .and_then(|s| super::file::version_from_name(&s.to_string_lossy()))
+        .or_else(|| {
+            cache
+                .read()
+                .unwrap()
+                .get::<HttpProviderCache>(HTTP_PROVIDER_ID)
+                .and_then(|c| c.url_versions.get(&spec.url).cloned())
+        });
+    Some(ModInfo {
+        provider: HTTP_PROVIDER_ID,
+        name,
+        spec: spec.clone(),
+        versions: vec![],
+        resolution: ModResolution::resolvable(spec.url.as_str().into()),
+        suggested_require: false,
+        suggested_dependencies: vec![],
+        modio_tags: None,
+        modio_id: None,
+        author: None,
+        version,
+    })
+}
+
 #[async_trait::async_trait]
 impl ModProvider for HttpProvider {
     async fn resolve_mod(
         &self,
         spec: &ModSpecification,
         _update: bool,
-        _cache: ProviderCache,
+        cache: ProviderCache,
     ) -> Result<ModResponse, ProviderError> {
-        let Ok(url) = url::Url::parse(&spec.url) else {
-            return Err(ProviderError::InvalidUrl {
-                url: spec.url.to_string(),
-            });
-        };
-
-        let name = url
-            .path_segments()
-            .and_then(|s| s.last())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| url.to_string());
-
-        Ok(ModResponse::Resolve(ModInfo {
-            provider: HTTP_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::resolvable(spec.url.as_str().into()),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        }))
+        let info = mod_info(spec, cache).context(InvalidUrlSnafu {
+            url: spec.url.to_string(),
+        })?;
+        Ok(ModResponse::Resolve(info))
     }
 
     async fn fetch_mod(
@@ -111,12 +161,7 @@ impl ModProvider for HttpProvider {
             if let Some(path) = if update {
                 None
             } else {
-                cache
-                    .read()
-                    .unwrap()
-                    .get::<HttpProviderCache>(HTTP_PROVIDER_ID)
-                    .and_then(|c| c.url_blobs.get(&url.0))
-                    .and_then(|r| blob_cache.get_path(r))
+                self.fetch_cached(res, cache.clone(), blob_cache)
             } {
                 if let Some(tx) = tx {
                     tx.send(FetchProgress::Complete {
@@ -127,20 +172,86 @@ impl ModProvider for HttpProvider {
                 }
                 path
             } else {
-                info!("downloading mod {url:?}...");
-                let response = self
-                    .client
-                    .get(&url.0)
-                    .send()
-                    .await
-                    .context(RequestFailedSnafu {
+                let mut writer = blob_cache.writer(&url.0)?;
+                if update || writer.validator().is_none() {
+                    // without a validator there is no telling whether the remote file changed
+                    // since the partial download
+                    writer.reset().with_context(|_| BufferIoSnafu {
                         url: url.0.to_string(),
-                    })?
-                    .error_for_status()
-                    .context(ResponseSnafu {
+                    })?;
+                }
+                let mut request = self.client.get(&url.0);
+                if writer.written() > 0
+                    && let Some(validator) = writer.validator()
+                {
+                    info!(
+                        "resuming download of mod {url:?} at {} bytes...",
+                        writer.written()
+                    );
+                    // the server sends the whole file instead if it changed in the meantime
+                    request = request
+                        .header(
+                            reqwest::header::RANGE,
+                            format!("bytes={}-", writer.written()),
+                        )
+                        .header(reqwest::header::IF_RANGE, validator);
+                } else {
+                    info!("downloading mod {url:?}...");
+                }
+                let mut response = request.send().await.context(RequestFailedSnafu {
+                    url: url.0.to_string(),
+                })?;
+                let range_mismatch = response.status() == reqwest::StatusCode::PARTIAL_CONTENT
+                    && content_range_start(response.headers()) != Some(writer.written());
+                if range_mismatch {
+                    warn!(
+                        "server did not resume {url:?} at {} bytes, downloading it again",
+                        writer.written()
+                    );
+                }
+                if range_mismatch
+                    || (writer.written() > 0
+                        && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE)
+                {
+                    // partial download can't be resumed, e.g. because the file got smaller or the
+                    // server sent a different range
+                    writer.reset().with_context(|_| BufferIoSnafu {
+                        url: url.0.to_string(),
+                    })?;
+                    response =
+                        self.client
+                            .get(&url.0)
+                            .send()
+                            .await
+                            .context(RequestFailedSnafu {
+                                url: url.0.to_string(),
+                            })?;
+                }
+                let response = response.error_for_status().context(ResponseSnafu {
+                    url: url.0.to_string(),
+                })?;
+                if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+                    // server ignored the range or the file changed and it sends the whole file
+                    writer.reset().with_context(|_| BufferIoSnafu {
                         url: url.0.to_string(),
                     })?;
-                let size = response.content_length(); // TODO will be incorrect if compressed
+                    writer
+                        .set_validator(resume_validator(response.headers()))
+                        .with_context(|_| BufferIoSnafu {
+                            url: url.0.to_string(),
+                        })?;
+                }
+                let header_version = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_DISPOSITION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(content_disposition_file_name)
+                    .and_then(|name| Path::new(name).file_stem())
+                    .and_then(|stem| super::file::version_from_name(&stem.to_string_lossy()));
+                // TODO will be incorrect if compressed
+                let size = response
+                    .content_length()
+                    .map(|remaining| writer.written() + remaining);
                 if let Some(mime) = response
                     .headers()
                     .get(reqwest::header::HeaderName::from_static("content-type"))
@@ -157,42 +268,28 @@ impl ModProvider for HttpProvider {
                     );
                 }
 
-                use futures::stream::TryStreamExt;
-                use tokio::io::AsyncWriteExt;
+                write_download(
+                    response.bytes_stream(),
+                    &mut writer,
+                    res,
+                    size,
+                    tx.as_ref(),
+                    FetchSnafu { url: &url.0 },
+                    BufferIoSnafu { url: &url.0 },
+                )
+                .await?;
 
-                let mut cursor = std::io::Cursor::new(vec![]);
-                let mut stream = response.bytes_stream();
-                while let Some(bytes) = stream.try_next().await.with_context(|_| FetchSnafu {
-                    url: url.0.to_string(),
-                })? {
-                    cursor
-                        .write_all(&bytes)
-                        .await
-                        .with_context(|_| BufferIoSnafu {
-                            url: url.0.to_string(),
-                        })?;
-                    if let Some(size) = size {
-                        if let Some(tx) = &tx {
-                            tx.send(FetchProgress::Progress {
-                                resolution: res.clone(),
-                                progress: cursor.get_ref().len() as u64,
-                                size,
-                            })
-                            .await
-                            .unwrap();
-                        }
+                let blob = writer.finish()?;
+                let path = blob_cache.get_path(&blob).unwrap();
+                {
+                    let mut lock = cache.write().unwrap();
+                    let http_cache = lock.get_mut::<HttpProviderCache>(HTTP_PROVIDER_ID);
+                    http_cache.url_blobs.insert(url.0.to_owned(), blob);
+                    if let Some(version) = header_version {
+                        http_cache.url_versions.insert(url.0.to_owned(), version);
                     }
                 }
 
-                let blob = blob_cache.write(&cursor.into_inner())?;
-                let path = blob_cache.get_path(&blob).unwrap();
-                cache
-                    .write()
-                    .unwrap()
-                    .get_mut::<HttpProviderCache>(HTTP_PROVIDER_ID)
-                    .url_blobs
-                    .insert(url.0.to_owned(), blob);
-
                 if let Some(tx) = tx {
                     tx.send(FetchProgress::Complete {
                         resolution: res.clone(),
@@ -213,24 +310,34 @@ impl ModProvider for HttpProvider {
         Ok(())
     }
 
-    fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
-        let url = url::Url::parse(&spec.url).ok()?;
-        let name = url
-            .path_segments()
-            .and_then(|s| s.last())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| url.to_string());
-        Some(ModInfo {
-            provider: HTTP_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::resolvable(spec.url.as_str().into()),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            modio_tags: None,
-            modio_id: None,
-        })
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        cache
+            .read()
+            .unwrap()
+            .get::<HttpProviderCache>(HTTP_PROVIDER_ID)
+            .and_then(|c| c.url_blobs.get(&res.url.0))
+            .and_then(|r| blob_cache.get_path(r))
+    }
+
+    fn cached_versions(
+        &self,
+        spec: &ModSpecification,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        let res = ModResolution::resolvable(spec.url.as_str().into());
+        self.fetch_cached(&res, cache, blob_cache)
+            .into_iter()
+            .collect()
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        mod_info(spec, cache)
     }
 
     fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
@@ -241,3 +348,25 @@ impl ModProvider for HttpProvider {
         Some("latest".to_string())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_content_range_start() {
+        let headers = |value: &'static str| {
+            reqwest::header::HeaderMap::from_iter([(
+                reqwest::header::CONTENT_RANGE,
+                reqwest::header::HeaderValue::from_static(value),
+            )])
+        };
+        assert_eq!(
+            content_range_start(&headers("bytes 100-199/200")),
+            Some(100)
+        );
+        assert_eq!(content_range_start(&headers("bytes 0-99/*")), Some(0));
+        assert_eq!(content_range_start(&headers("bytes */200")), None);
+        assert_eq!(content_range_start(&Default::default()), None);
+    }
+}
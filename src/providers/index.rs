@@ -0,0 +1,704 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: INDEX_PROVIDER_ID,
+        new: IndexProvider::new_provider,
+        can_provide: |url| re_index().is_match(url),
+        parameters: &[],
+    }
+}
+
+const INDEX_PROVIDER_ID: &str = "index";
+
+/// Highest [`ModIndex::format_version`] this version of mint understands.
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+static RE_INDEX: OnceLock<regex::Regex> = OnceLock::new();
+fn re_index() -> &'static regex::Regex {
+    RE_INDEX.get_or_init(|| {
+        regex::Regex::new(r"^index\+(?P<index>https?://[^#]+)#(?P<id>[^@]+)(?:@(?P<version>.+))?$")
+            .unwrap()
+    })
+}
+
+/// Manifest of a self-hosted mod index, a JSON file served from any web server. Mods in it are
+/// referred to as `index+<index url>#<mod id>`, optionally pinned to a version with
+/// `@<version>`:
+///
+/// ```json
+/// {
+///   "format_version": 1,
+///   "mods": {
+///     "better-scanner": {
+///       "name": "Better Scanner",
+///       "author": "Karl",
+///       "required": false,
+///       "approval": "Approved",
+///       "tags": ["qol"],
+///       "versions": [
+///         {
+///           "version": "1.1.0",
+///           "url": "better-scanner-1.1.0.pak",
+///           "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+///           "dependencies": ["ui-lib", "https://mod.io/g/drg/m/mod-hub"]
+///         }
+///       ]
+///     }
+///   }
+/// }
+/// ```
+///
+/// - `versions` are ordered newest first, the first one is used for unpinned mods.
+/// - `url` may be relative to the index.
+/// - `sha256` is checked against the downloaded file.
+/// - `dependencies` are ids of other mods in the same index or URLs of mods from any provider.
+/// - `author` is optional and shown in game together with the version.
+/// - `approval` is one of `Verified`, `Approved` and `Sandbox` (the default), `required` marks
+///   mods every lobby member needs and `tags` may contain `qol`, `gameplay`, `audio`, `visual`
+///   and `framework`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIndex {
+    pub format_version: u32,
+    pub mods: BTreeMap<String, IndexMod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexMod {
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub approval: Option<ApprovalStatus>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    pub versions: Vec<IndexModVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexModVersion {
+    pub version: String,
+    pub url: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+/// Parsed `index+` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IndexUrl {
+    index: String,
+    id: String,
+    version: Option<String>,
+}
+
+impl IndexUrl {
+    fn parse(url: &str) -> Option<Self> {
+        let captures = re_index().captures(url)?;
+        Some(Self {
+            index: captures["index"].to_string(),
+            id: captures["id"].to_string(),
+            version: captures.name("version").map(|m| m.as_str().to_string()),
+        })
+    }
+
+    fn spec(&self, id: &str, version: Option<&str>) -> ModSpecification {
+        ModSpecification::new(match version {
+            Some(version) => format!("index+{}#{id}@{version}", self.index),
+            None => format!("index+{}#{id}", self.index),
+        })
+    }
+
+    /// The mod and the version the URL refers to.
+    fn select<'a>(&self, index: &'a ModIndex) -> Option<(&'a IndexMod, &'a IndexModVersion)> {
+        let mod_ = index.mods.get(&self.id)?;
+        let version = match &self.version {
+            Some(version) => mod_.versions.iter().find(|v| &v.version == version)?,
+            None => mod_.versions.first()?,
+        };
+        Some((mod_, version))
+    }
+
+    /// Absolute download URL of `version`.
+    fn download_url(&self, version: &IndexModVersion) -> Result<String, ProviderError> {
+        url::Url::parse(&self.index)
+            .and_then(|index| index.join(&version.url))
+            .map(|url| url.to_string())
+            .ok()
+            .context(InvalidUrlSnafu { url: &version.url })
+    }
+
+    fn mod_info(
+        &self,
+        spec: &ModSpecification,
+        index: &ModIndex,
+    ) -> Result<ModInfo, ProviderError> {
+        let (mod_, version) = self.select(index).context(NoSuchIndexModSnafu {
+            url: spec.url.to_string(),
+        })?;
+        Ok(ModInfo {
+            provider: INDEX_PROVIDER_ID,
+            name: mod_.name.clone(),
+            spec: spec.clone(),
+            versions: mod_
+                .versions
+                .iter()
+                .map(|v| self.spec(&self.id, Some(&v.version)))
+                .collect(),
+            // fetched through this provider to check the file against the index
+            resolution: ModResolution::resolvable(
+                self.spec(&self.id, Some(&version.version)).url.into(),
+            ),
+            suggested_require: mod_.required,
+            suggested_dependencies: version
+                .dependencies
+                .iter()
+                .map(|dep| {
+                    if dep.contains("://") {
+                        ModSpecification::new(dep.clone())
+                    } else {
+                        self.spec(dep, None)
+                    }
+                })
+                .collect(),
+            modio_tags: Some(ModioTags {
+                qol: mod_.tags.contains("qol"),
+                gameplay: mod_.tags.contains("gameplay"),
+                audio: mod_.tags.contains("audio"),
+                visual: mod_.tags.contains("visual"),
+                framework: mod_.tags.contains("framework"),
+                versions: Default::default(),
+                required_status: if mod_.required {
+                    RequiredStatus::RequiredByAll
+                } else {
+                    RequiredStatus::Optional
+                },
+                approval_status: mod_.approval.unwrap_or(ApprovalStatus::Sandbox),
+            }),
+            modio_id: None,
+            author: mod_.author.clone(),
+            version: Some(version.version.clone()),
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IndexProviderCache {
+    indexes: HashMap<String, ModIndex>,
+    url_blobs: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for IndexProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl IndexProviderCache {
+    /// sha256 the cached index lists for the version `url` refers to.
+    fn expected_sha256(&self, url: &IndexUrl) -> Option<&str> {
+        let index = self.indexes.get(&url.index)?;
+        url.select(index).map(|(_, v)| v.sha256.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct IndexProvider {
+    client: reqwest::Client,
+}
+
+impl IndexProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn fetch_index(&self, url: &str) -> Result<ModIndex, ProviderError> {
+        info!("fetching mod index {url}...");
+        let bytes = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .context(RequestFailedSnafu { url })?
+            .error_for_status()
+            .context(ResponseSnafu { url })?
+            .bytes()
+            .await
+            .context(ResponseSnafu { url })?;
+        parse_index(url, &bytes)
+    }
+
+    fn cached_index(&self, url: &str, cache: &ProviderCache) -> Option<ModIndex> {
+        cache
+            .read()
+            .unwrap()
+            .get::<IndexProviderCache>(INDEX_PROVIDER_ID)
+            .and_then(|c| c.indexes.get(url))
+            .cloned()
+    }
+
+    async fn update_index(
+        &self,
+        url: &str,
+        cache: &ProviderCache,
+    ) -> Result<ModIndex, ProviderError> {
+        let index = self.fetch_index(url).await?;
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<IndexProviderCache>(INDEX_PROVIDER_ID)
+            .indexes
+            .insert(url.to_string(), index.clone());
+        Ok(index)
+    }
+
+    /// Index of `url` from the cache, fetched again on `update` or if the mod or version `url`
+    /// refers to is not in the cached index.
+    async fn index(
+        &self,
+        url: &IndexUrl,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<ModIndex, ProviderError> {
+        match self.cached_index(&url.index, cache) {
+            // a mod or version missing from the cached index may have been added since
+            Some(index) if !update && url.select(&index).is_some() => Ok(index),
+            _ => self.update_index(&url.index, cache).await,
+        }
+    }
+}
+
+fn parse_index(url: &str, data: &[u8]) -> Result<ModIndex, ProviderError> {
+    #[derive(Deserialize)]
+    struct Version {
+        format_version: u32,
+    }
+    let version = serde_json::from_slice::<Version>(data)
+        .context(InvalidIndexSnafu { url })?
+        .format_version;
+    ensure!(
+        version <= INDEX_FORMAT_VERSION,
+        UnsupportedIndexVersionSnafu { url, version }
+    );
+    serde_json::from_slice(data).context(InvalidIndexSnafu { url })
+}
+
+#[async_trait::async_trait]
+impl ModProvider for IndexProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+    ) -> Result<ModResponse, ProviderError> {
+        let url = IndexUrl::parse(&spec.url).context(InvalidUrlSnafu {
+            url: spec.url.to_string(),
+        })?;
+        let index = self.index(&url, update, &cache).await?;
+        Ok(ModResponse::Resolve(url.mod_info(spec, &index)?))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let path = if let Some(path) = if update {
+            None
+        } else {
+            self.fetch_cached(res, cache.clone(), blob_cache)
+        } {
+            path
+        } else {
+            let index_url = IndexUrl::parse(&res.url.0).context(InvalidUrlSnafu {
+                url: res.url.0.to_string(),
+            })?;
+            let index = self.index(&index_url, false, &cache).await?;
+            let (_, version) = index_url.select(&index).context(NoSuchIndexModSnafu {
+                url: res.url.0.to_string(),
+            })?;
+            let url = &index_url.download_url(version)?;
+
+            info!("downloading mod {url:?}...");
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .context(RequestFailedSnafu { url })?
+                .error_for_status()
+                .context(ResponseSnafu { url })?;
+            let size = response.content_length();
+
+            let mut writer = blob_cache.writer(url)?;
+            writer.reset().with_context(|_| BufferIoSnafu { url })?;
+
+            write_download(
+                response.bytes_stream(),
+                &mut writer,
+                res,
+                size,
+                tx.as_ref(),
+                FetchSnafu { url },
+                BufferIoSnafu { url },
+            )
+            .await?;
+
+            // checked before the file is moved into the cache so it does not keep a bad blob
+            let found = writer.hash();
+            if !version.sha256.eq_ignore_ascii_case(&found) {
+                writer.discard();
+                return IndexHashMismatchSnafu {
+                    url,
+                    expected: &version.sha256,
+                    found,
+                }
+                .fail();
+            }
+            let blob = writer.finish()?;
+            let path = blob_cache.get_path(&blob).unwrap();
+            cache
+                .write()
+                .unwrap()
+                .get_mut::<IndexProviderCache>(INDEX_PROVIDER_ID)
+                .url_blobs
+                .insert(res.url.0.to_owned(), blob);
+            path
+        };
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, cache: ProviderCache) -> Result<(), ProviderError> {
+        let urls = cache
+            .read()
+            .unwrap()
+            .get::<IndexProviderCache>(INDEX_PROVIDER_ID)
+            .map(|c| c.indexes.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for url in urls {
+            self.update_index(&url, &cache).await?;
+        }
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        let cache = cache.read().unwrap();
+        let prov = cache.get::<IndexProviderCache>(INDEX_PROVIDER_ID)?;
+        let blob = prov.url_blobs.get(&res.url.0)?;
+        // the index lists a different file for the version since it was downloaded
+        if let Some(expected) = IndexUrl::parse(&res.url.0)
+            .as_ref()
+            .and_then(|url| prov.expected_sha256(url))
+            && !expected.eq_ignore_ascii_case(blob.hash())
+        {
+            return None;
+        }
+        blob_cache.get_path(blob)
+    }
+
+    fn cached_versions(
+        &self,
+        spec: &ModSpecification,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        let Some(url) = IndexUrl::parse(&spec.url) else {
+            return vec![];
+        };
+        let cache = cache.read().unwrap();
+        let Some(prov) = cache.get::<IndexProviderCache>(INDEX_PROVIDER_ID) else {
+            return vec![];
+        };
+        prov.indexes
+            .get(&url.index)
+            .and_then(|index| index.mods.get(&url.id))
+            .into_iter()
+            .flat_map(|m| &m.versions)
+            .map(|v| url.spec(&url.id, Some(&v.version)))
+            .filter_map(|pinned| prov.url_blobs.get(&pinned.url))
+            .filter_map(|blob| blob_cache.get_path_unverified(blob))
+            .collect()
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        let url = IndexUrl::parse(&spec.url)?;
+        let index = self.cached_index(&url.index, &cache)?;
+        url.mod_info(spec, &index).ok()
+    }
+
+    fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        IndexUrl::parse(&spec.url).is_some_and(|url| url.version.is_some())
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        let url = IndexUrl::parse(&spec.url)?;
+        let index = self.cached_index(&url.index, &cache)?;
+        url.select(&index).map(|(_, v)| v.version.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INDEX: &str = r#"{
+        "format_version": 1,
+        "mods": {
+            "scanner": {
+                "name": "Better Scanner",
+                "author": "Karl",
+                "required": true,
+                "approval": "Verified",
+                "tags": ["qol"],
+                "versions": [
+                    {
+                        "version": "1.1",
+                        "url": "files/scanner-1.1.pak",
+                        "sha256": "aa",
+                        "dependencies": ["ui-lib", "https://mod.io/g/drg/m/mod-hub"]
+                    },
+                    { "version": "1.0", "url": "https://cdn.example.org/scanner-1.0.pak", "sha256": "bb" }
+                ]
+            },
+            "ui-lib": {
+                "name": "UI Lib",
+                "versions": [{ "version": "2", "url": "ui-lib.pak", "sha256": "cc" }]
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            IndexUrl::parse("index+https://example.org/mods/index.json#scanner@1.0"),
+            Some(IndexUrl {
+                index: "https://example.org/mods/index.json".into(),
+                id: "scanner".into(),
+                version: Some("1.0".into()),
+            })
+        );
+        assert_eq!(
+            IndexUrl::parse("https://example.org/mods/index.json#scanner"),
+            None
+        );
+        assert_eq!(
+            IndexUrl::parse("index+https://example.org/index.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mod_info() {
+        let index_url = "https://example.org/mods/index.json";
+        let index = parse_index(index_url, INDEX.as_bytes()).unwrap();
+
+        let spec = ModSpecification::new(format!("index+{index_url}#scanner"));
+        let url = IndexUrl::parse(&spec.url).unwrap();
+        let info = url.mod_info(&spec, &index).unwrap();
+        assert_eq!(info.name, "Better Scanner");
+        assert_eq!(info.author.as_deref(), Some("Karl"));
+        assert_eq!(info.version.as_deref(), Some("1.1"));
+        assert_eq!(
+            info.resolution.url.0,
+            format!("index+{index_url}#scanner@1.1")
+        );
+        assert_eq!(
+            url.download_url(&index.mods["scanner"].versions[0])
+                .unwrap(),
+            "https://example.org/mods/files/scanner-1.1.pak"
+        );
+        assert!(info.suggested_require);
+        assert_eq!(
+            info.suggested_dependencies,
+            [
+                ModSpecification::new(format!("index+{index_url}#ui-lib")),
+                ModSpecification::new("https://mod.io/g/drg/m/mod-hub".into()),
+            ]
+        );
+        assert_eq!(
+            info.versions,
+            [
+                ModSpecification::new(format!("index+{index_url}#scanner@1.1")),
+                ModSpecification::new(format!("index+{index_url}#scanner@1.0")),
+            ]
+        );
+        let tags = info.modio_tags.unwrap();
+        assert!(tags.qol && !tags.gameplay);
+        assert_eq!(tags.approval_status, ApprovalStatus::Verified);
+
+        let pinned = IndexUrl::parse(&info.versions[1].url).unwrap();
+        let info = pinned.mod_info(&info.versions[1], &index).unwrap();
+        assert_eq!(
+            info.resolution.url.0,
+            format!("index+{index_url}#scanner@1.0")
+        );
+        assert_eq!(
+            pinned
+                .download_url(&index.mods["scanner"].versions[1])
+                .unwrap(),
+            "https://cdn.example.org/scanner-1.0.pak"
+        );
+        assert!(info.suggested_dependencies.is_empty());
+
+        let spec = ModSpecification::new(format!("index+{index_url}#ui-lib"));
+        let info = IndexUrl::parse(&spec.url)
+            .unwrap()
+            .mod_info(&spec, &index)
+            .unwrap();
+        assert!(!info.suggested_require);
+        assert_eq!(
+            info.modio_tags.unwrap().approval_status,
+            ApprovalStatus::Sandbox
+        );
+
+        let spec = ModSpecification::new(format!("index+{index_url}#scanner@0.9"));
+        assert!(matches!(
+            IndexUrl::parse(&spec.url).unwrap().mod_info(&spec, &index),
+            Err(ProviderError::NoSuchIndexMod { .. })
+        ));
+    }
+
+    #[test]
+    fn test_format_version() {
+        assert!(matches!(
+            parse_index(
+                "https://example.org/index.json",
+                br#"{"format_version": 2, "mods": {}}"#
+            ),
+            Err(ProviderError::UnsupportedIndexVersion { version: 2, .. })
+        ));
+        assert!(matches!(
+            parse_index("https://example.org/index.json", br#"{"mods": {}}"#),
+            Err(ProviderError::InvalidIndex { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_through_store() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let index_url = format!("http://{}/mods/index.json", listener.local_addr().unwrap());
+        let index = serde_json::json!({
+            "format_version": 1,
+            "mods": {
+                "scanner": {
+                    "name": "Better Scanner",
+                    "versions": [{
+                        "version": "1.1",
+                        "url": "files/scanner.pak",
+                        "sha256": "f320bb595918c4496fc3184c22e929610569cfd47cfa8bf95eb88b0de880e0cd",
+                    }],
+                },
+                "tampered": {
+                    "name": "Tampered",
+                    "versions": [{ "version": "1", "url": "files/tampered.pak", "sha256": "aa" }],
+                },
+            },
+        });
+        let server = serve(
+            listener,
+            [
+                (
+                    "/mods/index.json".to_string(),
+                    serde_json::to_vec(&index).unwrap(),
+                ),
+                (
+                    "/mods/files/scanner.pak".to_string(),
+                    b"scanner pak".to_vec(),
+                ),
+                ("/mods/files/tampered.pak".to_string(), b"tampered".to_vec()),
+            ]
+            .into(),
+        )
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+        let spec = ModSpecification::new(format!("index+{index_url}#scanner"));
+
+        let (_, info) = store.resolve_mod(spec.clone(), false).await.unwrap();
+        let path = store
+            .fetch_mod(&info.resolution, false, None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"scanner pak");
+
+        let (_, tampered) = store
+            .resolve_mod(
+                ModSpecification::new(format!("index+{index_url}#tampered")),
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.fetch_mod(&tampered.resolution, false, None).await,
+            Err(ProviderError::IndexHashMismatch { .. })
+        ));
+        // the rejected download is not kept in the cache
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+
+        // the download was recorded by this provider so it is found offline and kept by gc
+        store.set_offline(true);
+        assert_eq!(
+            store
+                .fetch_mod(&info.resolution, false, None)
+                .await
+                .unwrap(),
+            path
+        );
+        let stats = store
+            .gc_blobs(&[spec], &Default::default(), 0, false)
+            .unwrap();
+        assert_eq!((stats.kept, stats.removed), (1, 0));
+        assert!(path.exists());
+
+        server.abort();
+    }
+}
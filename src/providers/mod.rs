@@ -1,11 +1,16 @@
+pub mod directory;
 pub mod file;
+pub mod git;
+pub mod github;
 pub mod http;
+pub mod index;
 pub mod modio;
 #[macro_use]
 pub mod cache;
 pub mod mod_store;
 
 use snafu::prelude::*;
+use snafu::IntoError;
 use tokio::sync::mpsc::Sender;
 
 use std::collections::HashMap;
@@ -64,6 +69,20 @@ pub trait ModProvider: Send + Sync {
     async fn update_cache(&self, cache: ProviderCache) -> Result<(), ProviderError>;
     /// Check if provider is configured correctly
     async fn check(&self) -> Result<(), ProviderError>;
+    /// Path to the mod if it is available without network access.
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf>;
+    /// Cached files of all versions of the mod, newest first.
+    fn cached_versions(
+        &self,
+        spec: &ModSpecification,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Vec<PathBuf>;
     fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo>;
     fn is_pinned(&self, spec: &ModSpecification, cache: ProviderCache) -> bool;
     fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String>;
@@ -120,6 +139,36 @@ pub enum ProviderError {
     AmbiguousModNameId { name_id: String },
     #[snafu(display("no mods returned for name \"{name_id}\""))]
     NoModsForNameId { name_id: String },
+    #[snafu(display("mod <{url}> is not cached and mint is in offline mode"))]
+    Offline { url: String },
+    #[snafu(display("git failed for <{url}>: {message}"))]
+    GitFailed { url: String, message: String },
+    #[snafu(display("I/O error while reading directory {}: {source}", path.display()))]
+    DirectoryIoError {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("no release matching <{url}>"))]
+    NoSuchRelease { url: String },
+    #[snafu(display("release {tag} of <{url}> has no matching asset"))]
+    NoMatchingReleaseAsset { url: String, tag: String },
+    #[snafu(display("invalid mod index <{url}>: {source}"))]
+    InvalidIndex {
+        source: serde_json::Error,
+        url: String,
+    },
+    #[snafu(display("mod index <{url}> has format version {version} which is not supported by this version of mint"))]
+    UnsupportedIndexVersion { url: String, version: u32 },
+    #[snafu(display("no mod or version in the index matching <{url}>"))]
+    NoSuchIndexMod { url: String },
+    #[snafu(display("sha256 of <{url}> is {found}, the index lists {expected}"))]
+    IndexHashMismatch {
+        url: String,
+        expected: String,
+        found: String,
+    },
+    #[snafu(display("failed to pack directory {}: {source}", path.display()))]
+    PackDirectoryFailed { source: repak::Error, path: PathBuf },
 }
 
 impl ProviderError {
@@ -160,3 +209,89 @@ pub struct ProviderParameter<'a> {
 }
 
 inventory::collect!(ProviderFactory);
+
+/// Stream a download into `writer`, reporting the progress of `res` to `tx` if the total `size`
+/// is known. Errors reading `stream` and writing the blob get the respective context.
+pub(super) async fn write_download<S, B, E, C, W>(
+    mut stream: S,
+    writer: &mut BlobWriter,
+    res: &ModResolution,
+    size: Option<u64>,
+    tx: Option<&Sender<FetchProgress>>,
+    stream_context: C,
+    write_context: W,
+) -> Result<(), ProviderError>
+where
+    S: futures::Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    C: IntoError<ProviderError, Source = E> + Clone,
+    W: IntoError<ProviderError, Source = std::io::Error> + Clone,
+{
+    use futures::stream::TryStreamExt;
+
+    while let Some(bytes) = stream
+        .try_next()
+        .await
+        .map_err(|e| stream_context.clone().into_error(e))?
+    {
+        writer
+            .write(bytes.as_ref())
+            .map_err(|e| write_context.clone().into_error(e))?;
+        if let Some(size) = size
+            && let Some(tx) = tx
+        {
+            tx.send(FetchProgress::Progress {
+                resolution: res.clone(),
+                progress: writer.written(),
+                size,
+            })
+            .await
+            .unwrap();
+        }
+    }
+    Ok(())
+}
+
+/// Serve the bodies of `routes` by request path over HTTP on `listener`.
+#[cfg(test)]
+async fn serve(
+    listener: tokio::net::TcpListener,
+    routes: HashMap<String, Vec<u8>>,
+) -> tokio::task::JoinHandle<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let read = stream.read(&mut buf).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend(&buf[..read]);
+            }
+            let request = String::from_utf8_lossy(&request);
+            let target = request.split(' ').nth(1).unwrap_or_default();
+            // routes with a query string match exactly, others match any query
+            let body = routes
+                .get(target)
+                .or_else(|| routes.get(target.split('?').next().unwrap()));
+            let response = match body {
+                Some(body) => [
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes(),
+                    body.clone(),
+                ]
+                .concat(),
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_vec(),
+            };
+            stream.write_all(&response).await.unwrap();
+        }
+    })
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use snafu::prelude::*;
 use tracing::*;
@@ -11,6 +12,69 @@ pub struct ModStore {
     providers: Providers,
     cache: ProviderCache,
     blob_cache: BlobCache,
+    /// Only use cached mod info and files, never access the network.
+    offline: AtomicBool,
+}
+
+/// Outcome of [`ModStore::gc_blobs`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BlobGcStats {
+    pub dry_run: bool,
+    pub kept: usize,
+    pub removed: usize,
+    /// Bytes freed by removing blobs, or that would be freed on a dry run.
+    pub freed_bytes: u64,
+}
+
+/// Outcome of [`ModStore::verify_blobs`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlobVerifyStats {
+    pub checked: usize,
+    /// Names of corrupt blobs that were deleted.
+    pub evicted: Vec<String>,
+}
+
+impl std::fmt::Display for BlobVerifyStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "checked {} cached mod files, evicted {} corrupt ones",
+            self.checked,
+            self.evicted.len()
+        )
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+impl std::fmt::Display for BlobGcStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} unused cached mod files ({}), {} {} in use",
+            if self.dry_run {
+                "would remove"
+            } else {
+                "removed"
+            },
+            self.removed,
+            format_size(self.freed_bytes),
+            if self.dry_run { "keeping" } else { "kept" },
+            self.kept,
+        )
+    }
 }
 
 impl ModStore {
@@ -42,9 +106,18 @@ impl ModStore {
             providers: RwLock::new(providers),
             cache: Arc::new(RwLock::new(cache)),
             blob_cache: BlobCache::new(cache_path.as_ref().join("blobs")),
+            offline: AtomicBool::new(false),
         })
     }
 
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
     pub fn get_provider_factories() -> impl Iterator<Item = &'static ProviderFactory> {
         inventory::iter::<ProviderFactory>()
     }
@@ -138,6 +211,16 @@ impl ModStore {
         original_spec: ModSpecification,
         update: bool,
     ) -> Result<(ModSpecification, ModInfo), ProviderError> {
+        if self.is_offline() {
+            let info = self
+                .get_provider(&original_spec.url)?
+                .get_mod_info(&original_spec, self.cache.clone())
+                .context(OfflineSnafu {
+                    url: original_spec.url.clone(),
+                })?;
+            return Ok((original_spec, info));
+        }
+
         let mut spec = original_spec.clone();
         loop {
             match self
@@ -195,6 +278,23 @@ impl ModStore {
         update: bool,
         tx: Option<Sender<FetchProgress>>,
     ) -> Result<PathBuf, ProviderError> {
+        if self.is_offline() {
+            let path = self
+                .get_provider(&res.url.0)?
+                .fetch_cached(res, self.cache.clone(), &self.blob_cache)
+                .context(OfflineSnafu {
+                    url: res.url.0.clone(),
+                })?;
+            if let Some(tx) = tx {
+                tx.send(FetchProgress::Complete {
+                    resolution: res.clone(),
+                })
+                .await
+                .unwrap();
+            }
+            return Ok(path);
+        }
+
         self.get_provider(&res.url.0)?
             .fetch_mod(
                 res,
@@ -207,6 +307,10 @@ impl ModStore {
     }
 
     pub async fn update_cache(&self) -> Result<(), ProviderError> {
+        if self.is_offline() {
+            warn!("offline mode, not updating cache");
+            return Ok(());
+        }
         let providers = self.providers.read().unwrap().clone();
         for (name, provider) in providers.iter() {
             info!("updating cache for {name} provider");
@@ -215,12 +319,110 @@ impl ModStore {
         Ok(())
     }
 
+    /// Delete cached mod files that are not used by `specs` or their dependencies. The version
+    /// each mod currently resolves to is kept along with its `keep` newest cached versions and
+    /// any blob named in `keep_hashes`, such as locked mods. Downloads that were interrupted and
+    /// not resumed within [`super::cache::ABANDONED_AFTER`] are deleted as well. Nothing is
+    /// deleted on a `dry_run`.
+    pub fn gc_blobs(
+        &self,
+        specs: &[ModSpecification],
+        keep_hashes: &HashSet<String>,
+        keep: usize,
+        dry_run: bool,
+    ) -> Result<BlobGcStats, ProviderError> {
+        // only reachability is needed here, hashing and evicting blobs is left to verify_blobs
+        // and fetching
+        let blob_cache = self.blob_cache.without_verification();
+        let mut used = keep_hashes.clone();
+        let mut seen = HashSet::new();
+        let mut queue = specs.to_vec();
+        while let Some(spec) = queue.pop() {
+            if !seen.insert(spec.clone()) {
+                continue;
+            }
+            let provider = match self.get_provider(&spec.url) {
+                Ok(provider) => provider,
+                // no provider can fetch it so nothing could have been cached
+                Err(ProviderError::ProviderNotFound { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+            let Some(info) = provider.get_mod_info(&spec, self.cache.clone()) else {
+                continue;
+            };
+
+            let resolved = provider.fetch_cached(&info.resolution, self.cache.clone(), &blob_cache);
+            let versions = provider.cached_versions(&spec, self.cache.clone(), &blob_cache);
+            for path in resolved.into_iter().chain(versions.into_iter().take(keep)) {
+                if let Some(name) = path.file_name() {
+                    used.insert(name.to_string_lossy().into_owned());
+                }
+            }
+            queue.extend(info.suggested_dependencies);
+        }
+
+        let mut stats = BlobGcStats {
+            dry_run,
+            ..Default::default()
+        };
+        for (name, size) in self.blob_cache.list()? {
+            if used.contains(&name) {
+                stats.kept += 1;
+                continue;
+            }
+            if dry_run {
+                debug!("would remove unused blob {name}");
+            } else {
+                debug!("removing unused blob {name}");
+                self.blob_cache.remove(&name)?;
+            }
+            stats.removed += 1;
+            stats.freed_bytes += size;
+        }
+        for (name, size) in self.blob_cache.list_abandoned()? {
+            if dry_run {
+                debug!("would remove abandoned download {name}");
+            } else {
+                debug!("removing abandoned download {name}");
+                self.blob_cache.remove(&name)?;
+            }
+            stats.removed += 1;
+            stats.freed_bytes += size;
+        }
+        info!("{stats}");
+        Ok(stats)
+    }
+
+    /// Check every cached mod file against its hash and delete corrupt ones. Providers fetch
+    /// evicted mods again the next time they are needed.
+    pub fn verify_blobs(&self) -> Result<BlobVerifyStats, ProviderError> {
+        let mut stats = BlobVerifyStats::default();
+        for (name, _) in self.blob_cache.list()? {
+            stats.checked += 1;
+            if !self.blob_cache.verify(&name)? {
+                warn!("cached blob {name} is corrupt, evicting it");
+                self.blob_cache.remove(&name)?;
+                stats.evicted.push(name);
+            }
+        }
+        info!("{stats}");
+        Ok(stats)
+    }
+
     pub fn get_mod_info(&self, spec: &ModSpecification) -> Option<ModInfo> {
         self.get_provider(&spec.url)
             .ok()?
             .get_mod_info(spec, self.cache.clone())
     }
 
+    /// Unpinned specification of the mod `spec` points to if known from the cache. Specifications
+    /// of the same mod map to the same canonical specification regardless of version.
+    pub fn canonical_spec(&self, spec: &ModSpecification) -> ModSpecification {
+        self.get_mod_info(spec)
+            .map(|info| info.spec)
+            .unwrap_or_else(|| spec.clone())
+    }
+
     pub fn is_pinned(&self, spec: &ModSpecification) -> bool {
         self.get_provider(&spec.url)
             .unwrap()
@@ -233,3 +435,108 @@ impl ModStore {
             .get_version_name(spec, self.cache.clone())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_offline_uncached() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(cache_dir.path(), &HashMap::new()).unwrap();
+        store.set_offline(true);
+
+        let url = "https://example.org/mods/test-mod.pak";
+        let (_, info) = store
+            .resolve_mod(ModSpecification::new(url.to_string()), false)
+            .await
+            .unwrap();
+        let err = store
+            .fetch_mod(&info.resolution, false, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Offline { url: u } if u == url));
+    }
+
+    #[test]
+    fn test_gc_blobs() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(cache_dir.path(), &HashMap::new()).unwrap();
+        let used = store.blob_cache.write(b"used").unwrap();
+        let unused = store.blob_cache.write(b"unused").unwrap();
+        let keep_hashes = HashSet::from([used.hash().to_string()]);
+
+        let stats = store.gc_blobs(&[], &keep_hashes, 0, true).unwrap();
+        assert_eq!(
+            stats,
+            BlobGcStats {
+                dry_run: true,
+                kept: 1,
+                removed: 1,
+                freed_bytes: 6,
+            }
+        );
+        assert!(store.blob_cache.get_path(&unused).is_some());
+
+        let stats = store.gc_blobs(&[], &keep_hashes, 0, false).unwrap();
+        assert_eq!(stats.removed, 1);
+        assert!(store.blob_cache.get_path(&unused).is_none());
+        assert!(store.blob_cache.get_path(&used).is_some());
+
+        let mut writer = store.blob_cache.writer("abandoned").unwrap();
+        writer.write(b"partial").unwrap();
+        writer.set_validator(Some("\"etag\"")).unwrap();
+        drop(writer);
+        let stats = store.gc_blobs(&[], &keep_hashes, 0, false).unwrap();
+        assert_eq!(
+            stats.removed, 0,
+            "recent partial downloads may still be in progress"
+        );
+
+        let blobs_dir = cache_dir.path().join("blobs");
+        for entry in std::fs::read_dir(&blobs_dir).unwrap() {
+            let path = entry.unwrap().path();
+            let modified = std::time::SystemTime::now() - crate::providers::cache::ABANDONED_AFTER;
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        let stats = store.gc_blobs(&[], &keep_hashes, 0, false).unwrap();
+        assert_eq!(stats.removed, 1);
+        assert_eq!(stats.freed_bytes, 7);
+        assert_eq!(std::fs::read_dir(&blobs_dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_verify_blobs() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(cache_dir.path(), &HashMap::new()).unwrap();
+        let intact = store.blob_cache.write(b"intact").unwrap();
+        let corrupt = store.blob_cache.write(b"corrupt").unwrap();
+        let corrupt_path = store.blob_cache.get_path(&corrupt).unwrap();
+        std::fs::write(&corrupt_path, b"corr").unwrap();
+
+        let stats = store.verify_blobs().unwrap();
+        assert_eq!(stats.checked, 2);
+        assert_eq!(stats.evicted, [corrupt.hash()]);
+        assert!(!corrupt_path.exists());
+        assert!(store.blob_cache.get_path(&intact).is_some());
+
+        // corrupt blobs are also caught when they are first requested
+        let corrupt = store.blob_cache.write(b"corrupt").unwrap();
+        let store = ModStore::new(cache_dir.path(), &HashMap::new()).unwrap();
+        std::fs::write(&corrupt_path, b"corr").unwrap();
+        assert!(store.blob_cache.get_path(&corrupt).is_none());
+        assert!(!corrupt_path.exists());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -6,6 +6,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use mockall::{automock, predicate::*};
 
 use ::modio;
+use md5::{Digest, Md5};
 
 use reqwest::{Request, Response};
 use reqwest_middleware::{Middleware, Next};
@@ -20,6 +21,16 @@ fn re_mod() -> &'static regex::Regex {
     RE_MOD.get_or_init(|| regex::Regex::new("^https://mod.io/g/drg/m/(?P<name_id>[^/#]+)(:?#(?P<mod_id>\\d+)(:?/(?P<modfile_id>\\d+))?)?$").unwrap())
 }
 
+/// Extract the modfile id from a pinned mod.io URL.
+pub fn parse_modfile_id(url: &str) -> Option<u32> {
+    re_mod()
+        .captures(url)?
+        .name("modfile_id")?
+        .as_str()
+        .parse()
+        .ok()
+}
+
 const MODIO_DRG_ID: u32 = 2475;
 const MODIO_PROVIDER_ID: &str = "modio";
 
@@ -103,6 +114,9 @@ pub struct ModioMod {
     latest_modfile: Option<u32>,
     modfiles: Vec<ModioFile>,
     tags: HashSet<String>,
+    /// Not available in caches written by older versions.
+    #[serde(default)]
+    author: Option<String>,
 }
 
 impl ModioMod {
@@ -113,8 +127,21 @@ impl ModioMod {
             latest_modfile: mod_.modfile.map(|f| f.id),
             modfiles: files.into_iter().map(ModioFile::new).collect(),
             tags: mod_.tags.into_iter().map(|t| t.name).collect(),
+            author: Some(mod_.submitted_by.username),
         }
     }
+
+    /// Version string of a modfile as entered by the author, falling back to the modfile ID.
+    fn modfile_version(&self, modfile_id: u32) -> String {
+        self.modfiles
+            .iter()
+            .find(|f| f.id == modfile_id)
+            .and_then(|f| f.version.as_deref())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| modfile_id.to_string())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,6 +161,11 @@ pub struct ModioFile {
     date_added: u64,
     version: Option<String>,
     changelog: Option<String>,
+    /// Not available in caches written by older versions.
+    #[serde(default)]
+    filesize: Option<u64>,
+    #[serde(default)]
+    md5: Option<String>,
 }
 impl ModioFile {
     fn new(file: modio::files::File) -> Self {
@@ -142,8 +174,37 @@ impl ModioFile {
             date_added: file.date_added,
             version: file.version,
             changelog: file.changelog,
+            filesize: Some(file.filesize),
+            md5: Some(file.filehash.md5),
         }
     }
+
+    /// Check a downloaded file against the size and md5 reported by mod.io.
+    fn verify(&self, mod_id: u32, size: u64, md5: &str) -> Result<(), DrgModioError> {
+        if let Some(expected) = self.filesize {
+            ensure!(
+                expected == size,
+                ModfileSizeMismatchSnafu {
+                    mod_id,
+                    modfile_id: self.id,
+                    expected,
+                    found: size,
+                }
+            );
+        }
+        if let Some(expected) = &self.md5 {
+            ensure!(
+                expected.eq_ignore_ascii_case(md5),
+                ModfileHashMismatchSnafu {
+                    mod_id,
+                    modfile_id: self.id,
+                    expected,
+                    found: md5,
+                }
+            );
+        }
+        Ok(())
+    }
 }
 
 #[derive(Default)]
@@ -219,6 +280,24 @@ pub enum DrgModioError {
         url: String,
         mod_id: u32,
     },
+    #[snafu(display(
+        "mod file {modfile_id} of mod {mod_id} has {found} bytes, mod.io reports {expected}"
+    ))]
+    ModfileSizeMismatch {
+        mod_id: u32,
+        modfile_id: u32,
+        expected: u64,
+        found: u64,
+    },
+    #[snafu(display(
+        "mod file {modfile_id} of mod {mod_id} has md5 {found}, mod.io reports {expected}"
+    ))]
+    ModfileHashMismatch {
+        mod_id: u32,
+        modfile_id: u32,
+        expected: String,
+        found: String,
+    },
     #[snafu(display("encountered mod.io-related error: {msg}"))]
     GenericError { msg: &'static str },
 }
@@ -229,7 +308,9 @@ impl DrgModioError {
             DrgModioError::FetchModFilesFailed { mod_id, .. }
             | DrgModioError::FetchModFileFailed { mod_id, .. }
             | DrgModioError::FetchModFailed { mod_id, .. }
-            | DrgModioError::FetchDependenciesFailed { mod_id, .. } => Some(*mod_id),
+            | DrgModioError::FetchDependenciesFailed { mod_id, .. }
+            | DrgModioError::ModfileSizeMismatch { mod_id, .. }
+            | DrgModioError::ModfileHashMismatch { mod_id, .. } => Some(*mod_id),
             _ => None,
         }
     }
@@ -510,11 +591,12 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
             url: url.to_string(),
         })?;
 
-        if let (Some(mod_id), Some(_modfile_id)) =
+        if let (Some(mod_id), Some(modfile_id)) =
             (captures.name("mod_id"), captures.name("modfile_id"))
         {
             // both mod ID and modfile ID specified, but not necessarily name
             let mod_id = mod_id.as_str().parse::<u32>().unwrap();
+            let modfile_id = modfile_id.as_str().parse::<u32>().unwrap();
 
             let mod_ =
                 if let Some(mod_) = read_cache(&cache, update, |c| c.mods.get(&mod_id).cloned()) {
@@ -594,10 +676,13 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
                 deps
             };
 
+            let version = mod_.modfile_version(modfile_id);
             Ok(ModResponse::Resolve(ModInfo {
                 provider: MODIO_PROVIDER_ID,
                 spec: format_spec(&mod_.name_id, mod_id, None),
                 name: mod_.name,
+                author: mod_.author,
+                version: Some(version),
                 versions: mod_
                     .modfiles
                     .into_iter()
@@ -723,15 +808,7 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
             let modfile_id = modfile_id.as_str().parse::<u32>().unwrap();
 
             Ok(
-                if let Some(path) = {
-                    let path = cache
-                        .read()
-                        .unwrap()
-                        .get::<ModioCache>(MODIO_PROVIDER_ID)
-                        .and_then(|c| c.modfile_blobs.get(&modfile_id))
-                        .and_then(|r| blob_cache.get_path(r));
-                    path
-                } {
+                if let Some(path) = self.fetch_cached(res, cache.clone(), blob_cache) {
                     if let Some(tx) = tx {
                         tx.send(FetchProgress::Complete {
                             resolution: res.clone(),
@@ -747,36 +824,40 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
                         .await?;
 
                     let size = file.filesize;
+                    let modfile = ModioFile::new(file.clone());
                     let download: modio::download::DownloadAction = file.into();
 
                     info!("downloading mod {url:?}...");
 
                     use futures::stream::TryStreamExt;
-                    use tokio::io::AsyncWriteExt;
 
-                    let mut cursor = std::io::Cursor::new(vec![]);
-                    let mut stream = Box::pin(self.modio.download(download).stream());
-                    while let Some(bytes) = stream
-                        .try_next()
-                        .await
-                        .with_context(|_| ModCtxtModioSnafu { mod_id })?
+                    let mut writer = blob_cache.writer(&res.url.0)?;
+                    // downloads through the mod.io client can't be resumed
+                    writer.reset().with_context(|_| ModCtxtIoSnafu { mod_id })?;
+                    let mut md5 = Md5::new();
+                    write_download(
+                        Box::pin(
+                            self.modio
+                                .download(download)
+                                .stream()
+                                .inspect_ok(|bytes| md5.update(bytes)),
+                        ),
+                        &mut writer,
+                        res,
+                        Some(size),
+                        tx.as_ref(),
+                        ModCtxtModioSnafu { mod_id },
+                        ModCtxtIoSnafu { mod_id },
+                    )
+                    .await?;
+
+                    if let Err(e) =
+                        modfile.verify(mod_id, writer.written(), &hex::encode(md5.finalize()))
                     {
-                        cursor
-                            .write_all(&bytes)
-                            .await
-                            .with_context(|_| ModCtxtIoSnafu { mod_id })?;
-                        if let Some(tx) = &tx {
-                            tx.send(FetchProgress::Progress {
-                                resolution: res.clone(),
-                                progress: cursor.get_ref().len() as u64,
-                                size,
-                            })
-                            .await
-                            .unwrap();
-                        }
+                        writer.discard();
+                        return Err(e.into());
                     }
-
-                    let blob = blob_cache.write(&cursor.into_inner())?;
+                    let blob = writer.finish()?;
                     let path = blob_cache.get_path(&blob).unwrap();
 
                     cache
@@ -900,6 +981,52 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
         self.modio.check().await.map_err(Into::into)
     }
 
+    fn fetch_cached(
+        &self,
+        res: &ModResolution,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<PathBuf> {
+        let modfile_id = parse_modfile_id(&res.url.0)?;
+        let path = cache
+            .read()
+            .unwrap()
+            .get::<ModioCache>(MODIO_PROVIDER_ID)
+            .and_then(|c| c.modfile_blobs.get(&modfile_id))
+            .and_then(|r| blob_cache.get_path(r));
+        path
+    }
+
+    fn cached_versions(
+        &self,
+        spec: &ModSpecification,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Vec<PathBuf> {
+        let Some(mod_id) = self
+            .get_mod_info(spec, cache.clone())
+            .and_then(|info| info.modio_id)
+        else {
+            return vec![];
+        };
+
+        let cache = cache.read().unwrap();
+        let Some(prov) = cache.get::<ModioCache>(MODIO_PROVIDER_ID) else {
+            return vec![];
+        };
+        let Some(mod_) = prov.mods.get(&mod_id) else {
+            return vec![];
+        };
+
+        let mut modfiles = mod_.modfiles.iter().collect::<Vec<_>>();
+        modfiles.sort_by_key(|f| std::cmp::Reverse(f.date_added));
+        modfiles
+            .into_iter()
+            .filter_map(|f| prov.modfile_blobs.get(&f.id))
+            .filter_map(|r| blob_cache.get_path_unverified(r))
+            .collect()
+    }
+
     fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
         let url = &spec.url;
         let captures = re_mod().captures(url)?;
@@ -936,6 +1063,8 @@ impl<M: DrgModio + Send + Sync> ModProvider for ModioProvider<M> {
             provider: MODIO_PROVIDER_ID,
             spec: format_spec(&mod_.name_id, mod_id, None),
             name: mod_.name.clone(),
+            author: mod_.author.clone(),
+            version: Some(mod_.modfile_version(modfile_id)),
             versions: mod_
                 .modfiles
                 .iter()
@@ -1046,6 +1175,40 @@ mod test {
     };
     use crate::state::config::ConfigWrapper;
 
+    #[test]
+    fn test_verify_modfile() {
+        let modfile = ModioFile {
+            id: 5,
+            date_added: 12345,
+            version: None,
+            changelog: None,
+            filesize: Some(3),
+            md5: Some("900150983CD24FB0D6963F7D28E17F72".to_string()),
+        };
+        assert!(modfile
+            .verify(3, 3, "900150983cd24fb0d6963f7d28e17f72")
+            .is_ok());
+        assert!(matches!(
+            modfile.verify(3, 4, "900150983cd24fb0d6963f7d28e17f72"),
+            Err(DrgModioError::ModfileSizeMismatch {
+                expected: 3,
+                found: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            modfile.verify(3, 3, "d41d8cd98f00b204e9800998ecf8427e"),
+            Err(DrgModioError::ModfileHashMismatch { modfile_id: 5, .. })
+        ));
+
+        let legacy = ModioFile {
+            filesize: None,
+            md5: None,
+            ..modfile
+        };
+        assert!(legacy.verify(3, 4, "").is_ok());
+    }
+
     #[tokio::test]
     async fn test_check_pass() {
         let mut mock = MockDrgModio::new();
@@ -1086,8 +1249,11 @@ mod test {
                             date_added: 12345,
                             version: None,
                             changelog: None,
+                            filesize: None,
+                            md5: None,
                         }],
                         tags: HashSet::new(),
+                        author: None,
                     },
                     dependencies: vec![],
                 },
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod share;
 
 use std::{
     collections::{BTreeMap, HashMap},
@@ -12,6 +13,10 @@ use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
 
 use self::config::ConfigWrapper;
+use crate::conflicts::ConflictRules;
+use crate::dependencies::MissingDependencyPolicy;
+use crate::history::BundleHistory;
+use crate::mod_lints::LintSuppression;
 use crate::{
     gui::GuiTheme,
     providers::{ModSpecification, ModStore},
@@ -21,7 +26,7 @@ use crate::{gui::SortBy, providers::ProviderError};
 use mint_lib::{mod_info::MetaConfig, DRGInstallation};
 
 /// Mod configuration, holds ModSpecification as well as other metadata
-#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModConfig {
     pub spec: ModSpecification,
     pub required: bool,
@@ -40,7 +45,7 @@ fn is_zero(value: &i32) -> bool {
     *value == 0
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModGroup {
     pub mods: Vec<ModConfig>,
 }
@@ -56,6 +61,16 @@ pub struct ModProfile {
     /// A profile can contain ordered individual mods mixed with mod groups.
     #[obake(cfg("0.1.0"))]
     pub mods: Vec<ModOrGroup>,
+
+    /// Per-path overrides of which mod wins a file contained in more than one mod.
+    #[obake(cfg("0.1.0"))]
+    #[serde(default, skip_serializing_if = "ConflictRules::is_empty")]
+    pub conflict_overrides: ConflictRules,
+
+    /// Lint findings to hide for this profile.
+    #[obake(cfg("0.1.0"))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lint_suppressions: Vec<LintSuppression>,
 }
 
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
@@ -65,6 +80,29 @@ pub enum ModOrGroup {
     Individual(ModConfig),
 }
 
+impl ModOrGroup {
+    /// Whether this is the mod with the URL `url`, or the group named `url` if `group` is set.
+    pub fn is(&self, url: &str, group: bool) -> bool {
+        match self {
+            ModOrGroup::Group { group_name, .. } => group && group_name == url,
+            ModOrGroup::Individual(mc) => !group && mc.spec.url == url,
+        }
+    }
+
+    pub fn set_enabled(&mut self, value: bool) {
+        match self {
+            ModOrGroup::Group { enabled, .. } => *enabled = value,
+            ModOrGroup::Individual(mc) => mc.enabled = value,
+        }
+    }
+}
+
+impl ModGroup {
+    pub fn get_mod_mut(&mut self, url: &str) -> Option<&mut ModConfig> {
+        self.mods.iter_mut().find(|mc| mc.spec.url == url)
+    }
+}
+
 impl From<ModProfile!["0.0.0"]> for ModProfile!["0.1.0"] {
     fn from(_legacy: ModProfile!["0.0.0"]) -> Self {
         // The migration requires `ModData` to handle instead.
@@ -163,6 +201,15 @@ impl ModData!["0.1.0"] {
         self.for_each_mod_predicate(profile, f, std::convert::identity, |mc| mc.enabled)
     }
 
+    /// Enabled mods of a profile in ascending order of precedence: by priority, with mods later in
+    /// the profile winning ties.
+    pub fn enabled_mods_by_priority(&self, profile: &str) -> Vec<ModConfig> {
+        let mut mods = vec![];
+        self.for_each_enabled_mod(profile, |mc| mods.push(mc.clone()));
+        mods.sort_by_key(|mc| mc.priority);
+        mods
+    }
+
     pub fn for_each_mod_mut<F: FnMut(&mut ModConfig)>(&mut self, profile: &str, f: F) {
         self.for_each_mod_predicate_mut(profile, f, |_| true, |_| true)
     }
@@ -245,6 +292,8 @@ impl From<ModData!["0.0.0"]> for ModData!["0.1.0"] {
                     .into_iter()
                     .map(ModOrGroup::Individual)
                     .collect(),
+                conflict_overrides: Default::default(),
+                lint_suppressions: Default::default(),
             };
             new_profiles.push((name, new_profile));
         }
@@ -329,6 +378,122 @@ impl ModData!["0.1.0"] {
         self.profiles.remove(&self.active_profile);
         self.active_profile = self.profiles.keys().next().unwrap().to_string();
     }
+
+    pub fn get_profile_mut(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut ModProfile!["0.1.0"], ModDataError> {
+        self.profiles
+            .get_mut(name)
+            .context(NoSuchProfileSnafu { name })
+    }
+
+    pub fn get_group_mut(&mut self, name: &str) -> Result<&mut ModGroup, ModDataError> {
+        self.groups.get_mut(name).context(NoSuchGroupSnafu { name })
+    }
+
+    pub fn set_active_profile(&mut self, name: &str) -> Result<(), ModDataError> {
+        ensure!(
+            self.profiles.contains_key(name),
+            NoSuchProfileSnafu { name }
+        );
+        self.active_profile = name.to_string();
+        Ok(())
+    }
+
+    pub fn create_profile(&mut self, name: &str) -> Result<(), ModDataError> {
+        ensure!(
+            !self.profiles.contains_key(name),
+            ProfileExistsSnafu { name }
+        );
+        self.profiles.insert(name.to_string(), Default::default());
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<(), ModDataError> {
+        ensure!(
+            !self.profiles.contains_key(new_name),
+            ProfileExistsSnafu { name: new_name }
+        );
+        let profile = self
+            .profiles
+            .remove(name)
+            .context(NoSuchProfileSnafu { name })?;
+        self.profiles.insert(new_name.to_string(), profile);
+        if self.active_profile == name {
+            self.active_profile = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Delete a profile. The last remaining profile cannot be deleted.
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), ModDataError> {
+        ensure!(
+            self.profiles.contains_key(name),
+            NoSuchProfileSnafu { name }
+        );
+        ensure!(self.profiles.len() > 1, LastProfileSnafu);
+        if self.active_profile == name {
+            self.remove_active_profile();
+        } else {
+            self.profiles.remove(name);
+        }
+        Ok(())
+    }
+
+    /// Profiles containing the group `name`.
+    pub fn profiles_using_group(&self, name: &str) -> Vec<String> {
+        self.profiles
+            .iter()
+            .filter(|(_, p)| p.mods.iter().any(|m| m.is(name, true)))
+            .map(|(n, _)| n.clone())
+            .collect()
+    }
+
+    pub fn create_group(&mut self, name: &str) -> Result<(), ModDataError> {
+        ensure!(!self.groups.contains_key(name), GroupExistsSnafu { name });
+        self.groups.insert(name.to_string(), Default::default());
+        Ok(())
+    }
+
+    /// Rename a group, updating the profiles that contain it.
+    pub fn rename_group(&mut self, name: &str, new_name: &str) -> Result<(), ModDataError> {
+        ensure!(
+            !self.groups.contains_key(new_name),
+            GroupExistsSnafu { name: new_name }
+        );
+        let group = self
+            .groups
+            .remove(name)
+            .context(NoSuchGroupSnafu { name })?;
+        self.groups.insert(new_name.to_string(), group);
+        for profile in self.profiles.values_mut() {
+            for m in &mut profile.mods {
+                if let ModOrGroup::Group { group_name, .. } = m
+                    && group_name == name
+                {
+                    *group_name = new_name.to_string();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete a group. A group still contained in a profile can only be deleted if `force` is set,
+    /// which also removes it from those profiles.
+    pub fn delete_group(&mut self, name: &str, force: bool) -> Result<(), ModDataError> {
+        ensure!(self.groups.contains_key(name), NoSuchGroupSnafu { name });
+        let profiles = self.profiles_using_group(name);
+        ensure!(
+            force || profiles.is_empty(),
+            GroupInUseSnafu { name, profiles }
+        );
+        for profile in self.profiles.values_mut() {
+            profile.mods.retain(|m| !m.is(name, true));
+        }
+        self.groups.remove(name);
+        Ok(())
+    }
 }
 
 #[obake::versioned]
@@ -339,6 +504,18 @@ pub struct Config {
     pub drg_pak_path: Option<PathBuf>,
     pub gui_theme: Option<GuiTheme>,
     pub sorting_config: Option<SortingConfig>,
+    #[serde(default)]
+    pub missing_dependencies: MissingDependencyPolicy,
+    /// Only use cached mod info and files, never access the network.
+    #[serde(default)]
+    pub offline: bool,
+    /// Number of previously integrated mod bundles to keep for rolling back.
+    #[serde(default = "default_bundle_history")]
+    pub bundle_history: usize,
+}
+
+fn default_bundle_history() -> usize {
+    crate::history::DEFAULT_KEEP
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -413,6 +590,9 @@ impl Default for Config!["0.0.0"] {
                 .map(DRGInstallation::main_pak),
             gui_theme: None,
             sorting_config: None,
+            missing_dependencies: Default::default(),
+            offline: false,
+            bundle_history: default_bundle_history(),
         }
     }
 }
@@ -423,6 +603,22 @@ impl From<&VersionAnnotatedConfig> for MetaConfig {
     }
 }
 
+#[derive(Debug, Snafu)]
+pub enum ModDataError {
+    #[snafu(display("profile {name:?} does not exist"))]
+    NoSuchProfile { name: String },
+    #[snafu(display("profile {name:?} already exists"))]
+    ProfileExists { name: String },
+    #[snafu(display("cannot delete the last profile"))]
+    LastProfile,
+    #[snafu(display("group {name:?} does not exist"))]
+    NoSuchGroup { name: String },
+    #[snafu(display("group {name:?} already exists"))]
+    GroupExists { name: String },
+    #[snafu(display("group {name:?} is still used by profiles {profiles:?}"))]
+    GroupInUse { name: String, profiles: Vec<String> },
+}
+
 #[derive(Debug, Snafu)]
 pub enum StateError {
     #[snafu(display("failed to deserialize user config"))]
@@ -468,7 +664,9 @@ impl State {
         let mod_data = ConfigWrapper::<VersionAnnotatedModData>::new(mod_data_path, mod_data);
         mod_data.save().unwrap();
 
-        let store = ModStore::new(&dirs.cache_dir, &config.provider_parameters)?.into();
+        let store = ModStore::new(&dirs.cache_dir, &config.provider_parameters)?;
+        store.set_offline(config.offline);
+        let store = store.into();
 
         Ok(Self {
             dirs,
@@ -477,6 +675,11 @@ impl State {
             store,
         })
     }
+
+    /// History of integrated mod bundles in the data directory.
+    pub fn history(&self) -> BundleHistory {
+        BundleHistory::new(&self.dirs.data_dir, self.config.bundle_history)
+    }
 }
 
 fn read_config_or_default(config_path: &PathBuf) -> Result<VersionAnnotatedConfig, StateError> {
@@ -580,6 +783,7 @@ mod mod_data_tests {
                             enabled: false,
                         },
                     ],
+                    ..Default::default()
                 },
             )]
             .into(),
@@ -634,6 +838,7 @@ mod mod_data_tests {
                             enabled: true,
                         },
                     ],
+                    ..Default::default()
                 },
             )]
             .into(),
@@ -688,6 +893,7 @@ mod mod_data_tests {
                             enabled: true,
                         },
                     ],
+                    ..Default::default()
                 },
             )]
             .into(),
@@ -703,4 +909,48 @@ mod mod_data_tests {
         let any_required = mod_data.any_mod("default", |mc, _| mc.required);
         assert!(any_required);
     }
+
+    #[test]
+    fn test_manage_profiles_and_groups() {
+        use super::ModDataError;
+
+        let mut mod_data = ModData::default();
+        assert!(matches!(
+            mod_data.delete_profile("default"),
+            Err(ModDataError::LastProfile)
+        ));
+
+        mod_data.create_profile("dev").unwrap();
+        assert!(matches!(
+            mod_data.create_profile("dev"),
+            Err(ModDataError::ProfileExists { .. })
+        ));
+        mod_data.set_active_profile("dev").unwrap();
+        mod_data.rename_profile("dev", "test").unwrap();
+        assert_eq!(mod_data.active_profile, "test");
+
+        mod_data.create_group("libs").unwrap();
+        mod_data
+            .get_profile_mut("test")
+            .unwrap()
+            .mods
+            .push(ModOrGroup::Group {
+                group_name: "libs".to_string(),
+                enabled: true,
+            });
+        mod_data.rename_group("libs", "deps").unwrap();
+        assert!(mod_data.profiles["test"].mods[0].is("deps", true));
+        assert_eq!(mod_data.profiles_using_group("deps"), ["test"]);
+
+        assert!(matches!(
+            mod_data.delete_group("deps", false),
+            Err(ModDataError::GroupInUse { .. })
+        ));
+        mod_data.delete_group("deps", true).unwrap();
+        assert!(mod_data.profiles["test"].mods.is_empty());
+        assert!(!mod_data.groups.contains_key("deps"));
+
+        mod_data.delete_profile("test").unwrap();
+        assert_eq!(mod_data.active_profile, "default");
+    }
 }
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use super::{ModData_v0_1_0 as ModData, ModGroup, ModOrGroup, ModProfile_v0_1_0 as ModProfile};
+
+/// Prefix of share codes, distinguishing them from mod URLs.
+const SHARE_CODE_PREFIX: &str = "mint:";
+
+/// A profile together with the mod groups it references, in a form that can be imported into
+/// another installation.
+#[obake::versioned]
+#[obake(version("0.0.0"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedProfile {
+    pub name: String,
+    pub profile: ModProfile,
+    pub groups: BTreeMap<String, ModGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "version")]
+pub enum VersionAnnotatedSharedProfile {
+    #[serde(rename = "0.0.0")]
+    V0_0_0(SharedProfile!["0.0.0"]),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Snafu)]
+pub enum ShareError {
+    #[snafu(display("profile {name:?} does not exist"))]
+    NoSuchProfile { name: String },
+    #[snafu(display("invalid share code: {source}"))]
+    InvalidShareCode { source: base64::DecodeError },
+    #[snafu(display("invalid share code: {source}"))]
+    DecompressFailed { source: std::io::Error },
+    #[snafu(display("invalid shared profile: {source}"))]
+    InvalidSharedProfile { source: serde_json::Error },
+    #[snafu(display("shared profile was exported by a newer version of mint"))]
+    UnsupportedShareVersion,
+    #[snafu(display("shared profile references group {name:?} which it does not contain"))]
+    MissingGroup { name: String },
+}
+
+/// What to do when an imported profile or group has the same name as an existing one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportMode {
+    /// Import under a new name such as "name (2)".
+    #[default]
+    Rename,
+    /// Add mods the existing profile or group does not contain yet.
+    Merge,
+}
+
+/// Whether `input` looks like a share code rather than e.g. a list of mod URLs.
+pub fn is_share_code(input: &str) -> bool {
+    input.trim().starts_with(SHARE_CODE_PREFIX)
+}
+
+/// `name`, or `name (n)` for the lowest `n` such that the name is not taken.
+fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{name} ({n})"))
+        .find(|n| !taken(n))
+        .unwrap()
+}
+
+impl SharedProfile!["0.0.0"] {
+    /// Snapshot `profile` and the groups it references.
+    pub fn export(mod_data: &ModData, name: &str) -> Result<Self, ShareError> {
+        let profile = mod_data
+            .profiles
+            .get(name)
+            .context(NoSuchProfileSnafu { name })?
+            .clone();
+        let groups = profile
+            .mods
+            .iter()
+            .filter_map(|m| match m {
+                ModOrGroup::Group { group_name, .. } => mod_data
+                    .groups
+                    .get(group_name)
+                    .map(|g| (group_name.clone(), g.clone())),
+                ModOrGroup::Individual(_) => None,
+            })
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            profile,
+            groups,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&VersionAnnotatedSharedProfile::V0_0_0(self.clone())).unwrap()
+    }
+
+    /// Compact form for pasting in chat: deflated JSON encoded as URL safe base64.
+    pub fn to_share_code(&self) -> String {
+        let json =
+            serde_json::to_vec(&VersionAnnotatedSharedProfile::V0_0_0(self.clone())).unwrap();
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&json).unwrap();
+        let compressed = encoder.finish().unwrap();
+        format!(
+            "{SHARE_CODE_PREFIX}{}",
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed)
+        )
+    }
+
+    /// Parse either the JSON or the share code form.
+    pub fn parse(input: &str) -> Result<Self, ShareError> {
+        let input = input.trim();
+        let json = if let Some(code) = input.strip_prefix(SHARE_CODE_PREFIX) {
+            let compressed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(code)
+                .context(InvalidShareCodeSnafu)?;
+            let mut json = vec![];
+            flate2::read::DeflateDecoder::new(compressed.as_slice())
+                .read_to_end(&mut json)
+                .context(DecompressFailedSnafu)?;
+            json
+        } else {
+            input.as_bytes().to_vec()
+        };
+        let shared = match serde_json::from_slice::<VersionAnnotatedSharedProfile>(&json)
+            .context(InvalidSharedProfileSnafu)?
+        {
+            VersionAnnotatedSharedProfile::V0_0_0(shared) => shared,
+            VersionAnnotatedSharedProfile::Unsupported => UnsupportedShareVersionSnafu.fail()?,
+        };
+        for m in &shared.profile.mods {
+            if let ModOrGroup::Group { group_name, .. } = m {
+                ensure!(
+                    shared.groups.contains_key(group_name),
+                    MissingGroupSnafu { name: group_name }
+                );
+            }
+        }
+        Ok(shared)
+    }
+
+    /// Add the profile and its groups to `mod_data`, returning the name of the imported profile.
+    pub fn import(self, mod_data: &mut ModData, mode: ImportMode) -> String {
+        let mut profile = self.profile;
+
+        for (name, group) in self.groups {
+            let new_name = match mod_data.groups.get_mut(&name) {
+                None => {
+                    mod_data.groups.insert(name.clone(), group);
+                    continue;
+                }
+                Some(existing) if mode == ImportMode::Merge => {
+                    for mc in group.mods {
+                        if !existing.mods.iter().any(|m| m.spec == mc.spec) {
+                            existing.mods.push(mc);
+                        }
+                    }
+                    continue;
+                }
+                Some(existing) if existing.mods == group.mods => continue,
+                Some(_) => {
+                    let new_name = unique_name(&name, |n| mod_data.groups.contains_key(n));
+                    mod_data.groups.insert(new_name.clone(), group);
+                    new_name
+                }
+            };
+            for m in &mut profile.mods {
+                if let ModOrGroup::Group { group_name, .. } = m
+                    && *group_name == name
+                {
+                    *group_name = new_name.clone();
+                }
+            }
+        }
+
+        match mod_data.profiles.get_mut(&self.name) {
+            None => {
+                mod_data.profiles.insert(self.name.clone(), profile);
+                self.name
+            }
+            Some(existing) if mode == ImportMode::Merge => {
+                for m in profile.mods {
+                    let present = existing.mods.iter().any(|e| match (e, &m) {
+                        (ModOrGroup::Individual(a), ModOrGroup::Individual(b)) => a.spec == b.spec,
+                        (
+                            ModOrGroup::Group { group_name: a, .. },
+                            ModOrGroup::Group { group_name: b, .. },
+                        ) => a == b,
+                        _ => false,
+                    });
+                    if !present {
+                        existing.mods.push(m);
+                    }
+                }
+                for (path, spec) in profile.conflict_overrides.overrides {
+                    existing
+                        .conflict_overrides
+                        .overrides
+                        .entry(path)
+                        .or_insert(spec);
+                }
+                for suppression in profile.lint_suppressions {
+                    if !existing.lint_suppressions.contains(&suppression) {
+                        existing.lint_suppressions.push(suppression);
+                    }
+                }
+                self.name
+            }
+            Some(_) => {
+                let name = unique_name(&self.name, |n| mod_data.profiles.contains_key(n));
+                mod_data.profiles.insert(name.clone(), profile);
+                name
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::providers::ModSpecification;
+    use crate::state::ModConfig;
+
+    fn mod_config(url: &str) -> ModConfig {
+        ModConfig {
+            spec: ModSpecification::new(url.to_string()),
+            required: true,
+            enabled: false,
+            priority: 3,
+        }
+    }
+
+    fn mod_data() -> ModData {
+        let mut mod_data = ModData::default();
+        mod_data.groups.insert(
+            "libs".to_string(),
+            ModGroup {
+                mods: vec![mod_config("https://mod.io/g/drg/m/lib#1/2")],
+            },
+        );
+        mod_data.profiles.insert(
+            "dev".to_string(),
+            ModProfile {
+                mods: vec![
+                    ModOrGroup::Individual(mod_config("a.pak")),
+                    ModOrGroup::Group {
+                        group_name: "libs".to_string(),
+                        enabled: true,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        mod_data
+    }
+
+    fn specs(profile: &ModProfile) -> Vec<String> {
+        profile
+            .mods
+            .iter()
+            .map(|m| match m {
+                ModOrGroup::Individual(mc) => mc.spec.url.clone(),
+                ModOrGroup::Group { group_name, .. } => format!("group {group_name}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let shared = SharedProfile::export(&mod_data(), "dev").unwrap();
+        assert_eq!(shared.groups.len(), 1);
+
+        for encoded in [shared.to_json(), shared.to_share_code()] {
+            let parsed = SharedProfile::parse(&encoded).unwrap();
+            assert_eq!(parsed.name, "dev");
+            assert_eq!(specs(&parsed.profile), specs(&shared.profile));
+            let ModOrGroup::Individual(mc) = &parsed.profile.mods[0] else {
+                panic!("expected a mod");
+            };
+            assert!(mc.required && !mc.enabled && mc.priority == 3);
+            assert_eq!(parsed.groups["libs"].mods, shared.groups["libs"].mods);
+        }
+
+        assert!(SharedProfile::parse("mint:!!").is_err());
+        assert!(matches!(
+            SharedProfile::parse(r#"{"version": "9.0.0"}"#),
+            Err(ShareError::UnsupportedShareVersion)
+        ));
+    }
+
+    #[test]
+    fn test_import() {
+        let shared = SharedProfile::export(&mod_data(), "dev").unwrap();
+
+        let mut target = mod_data();
+        target.groups.get_mut("libs").unwrap().mods = vec![mod_config("other.pak")];
+        let name = shared.clone().import(&mut target, ImportMode::Rename);
+        assert_eq!(name, "dev (2)");
+        assert_eq!(specs(&target.profiles[&name]), ["a.pak", "group libs (2)"]);
+        assert_eq!(target.groups["libs (2)"].mods, shared.groups["libs"].mods);
+
+        let mut target = mod_data();
+        target.profiles.get_mut("dev").unwrap().mods =
+            vec![ModOrGroup::Individual(mod_config("b.pak"))];
+        target.groups.get_mut("libs").unwrap().mods = vec![mod_config("other.pak")];
+        let name = shared.import(&mut target, ImportMode::Merge);
+        assert_eq!(name, "dev");
+        assert_eq!(
+            specs(&target.profiles["dev"]),
+            ["b.pak", "a.pak", "group libs"]
+        );
+        assert_eq!(target.groups["libs"].mods.len(), 2);
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::providers::directory::list_files;
+use crate::providers::ModSpecification;
+
+/// How often local mods are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Size and modification time of every file of a local mod, keyed by path relative to the mod.
+/// A single file mod has one entry with an empty path.
+type Stamp = BTreeMap<String, (u64, Option<SystemTime>)>;
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let file_stamp = |path: &Path| {
+        let metadata = path.metadata().ok()?;
+        Some((metadata.len(), metadata.modified().ok()))
+    };
+    if path.is_dir() {
+        list_files(path)
+            .ok()?
+            .into_iter()
+            .map(|(rel, path)| Some((rel, file_stamp(&path)?)))
+            .collect()
+    } else {
+        Some([(String::new(), file_stamp(path)?)].into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// Change of a local mod since it was last integrated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModChange {
+    pub spec: ModSpecification,
+    pub kind: ModChangeKind,
+    /// Number of files added, modified or removed, always 1 for single file mods.
+    pub files: usize,
+}
+
+impl Display for ModChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.kind {
+            ModChangeKind::Added => '+',
+            ModChangeKind::Modified => '~',
+            ModChangeKind::Removed => '-',
+        };
+        write!(f, "{sign} {}", self.spec.url)?;
+        if self.files > 1 {
+            write!(f, " ({} files)", self.files)?;
+        }
+        Ok(())
+    }
+}
+
+fn changed_files(old: &Stamp, new: &Stamp) -> usize {
+    old.iter().filter(|(k, v)| new.get(*k) != Some(v)).count()
+        + new.keys().filter(|k| !old.contains_key(*k)).count()
+}
+
+/// Watches the local file and directory mods of a set of mods by polling their files.
+///
+/// A change is only reported once the files have stopped changing for the debounce duration so a
+/// cook writing many files results in a single re-integration.
+pub struct ModWatcher {
+    debounce: Duration,
+    /// Stamps of the mods as of the last reported change.
+    stamps: BTreeMap<ModSpecification, Option<Stamp>>,
+    /// Stamps differing from `stamps` and when they were first seen.
+    pending: Option<(BTreeMap<ModSpecification, Option<Stamp>>, Instant)>,
+}
+
+/// Whether `spec` refers to a local file or directory, which may not exist yet.
+fn is_local(spec: &ModSpecification) -> bool {
+    let url = &spec.url;
+    Path::new(url).exists() || !(url.contains("://") || url.starts_with("git+"))
+}
+
+impl ModWatcher {
+    /// Watch the mods of `specs` which are local files or directories. Paths that don't exist yet
+    /// are reported as added once they appear. Mods from other providers are ignored.
+    pub fn new(specs: &[ModSpecification], debounce: Duration) -> Self {
+        let stamps = specs
+            .iter()
+            .filter(|spec| is_local(spec))
+            .map(|spec| (spec.clone(), stamp(Path::new(&spec.url))))
+            .collect();
+        Self {
+            debounce,
+            stamps,
+            pending: None,
+        }
+    }
+
+    pub fn specs(&self) -> impl Iterator<Item = &ModSpecification> {
+        self.stamps.keys()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stamps.is_empty()
+    }
+
+    /// Check the watched mods for changes, returning them once they have settled.
+    pub fn poll(&mut self) -> Option<Vec<ModChange>> {
+        let current = self
+            .stamps
+            .keys()
+            .map(|spec| (spec.clone(), stamp(Path::new(&spec.url))))
+            .collect::<BTreeMap<_, _>>();
+
+        if current == self.stamps {
+            self.pending = None;
+            return None;
+        }
+        match &self.pending {
+            Some((pending, since)) if *pending == current => {
+                if since.elapsed() < self.debounce {
+                    return None;
+                }
+            }
+            _ => {
+                self.pending = Some((current, Instant::now()));
+                return None;
+            }
+        }
+
+        let (current, _) = self.pending.take().unwrap();
+        let changes = self
+            .stamps
+            .iter()
+            .zip(&current)
+            .filter_map(|((spec, old), (_, new))| {
+                let (kind, files) = match (old, new) {
+                    (Some(old), Some(new)) if old != new => {
+                        (ModChangeKind::Modified, changed_files(old, new))
+                    }
+                    (None, Some(new)) => (ModChangeKind::Added, new.len()),
+                    (Some(old), None) => (ModChangeKind::Removed, old.len()),
+                    _ => return None,
+                };
+                Some(ModChange {
+                    spec: spec.clone(),
+                    kind,
+                    files,
+                })
+            })
+            .collect();
+        self.stamps = current;
+        Some(changes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fs_err as fs;
+
+    use super::*;
+
+    #[test]
+    fn test_poll() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak = dir.path().join("test.pak");
+        let mod_dir = dir.path().join("mod");
+        fs::write(&pak, b"pak").unwrap();
+        fs::create_dir_all(mod_dir.join("FSD/Content")).unwrap();
+        fs::write(mod_dir.join("FSD/Content/A.uasset"), b"a").unwrap();
+
+        let later_dir = dir.path().join("later");
+
+        let specs = [
+            ModSpecification::new(pak.to_string_lossy().to_string()),
+            ModSpecification::new(mod_dir.to_string_lossy().to_string()),
+            ModSpecification::new("https://mod.io/g/drg/m/test".to_string()),
+            ModSpecification::new(later_dir.to_string_lossy().to_string()),
+        ];
+        let mut watcher = ModWatcher::new(&specs, Duration::ZERO);
+        assert_eq!(watcher.specs().count(), 3);
+        assert_eq!(watcher.poll(), None);
+
+        fs::write(mod_dir.join("FSD/Content/A.uasset"), b"changed").unwrap();
+        fs::write(mod_dir.join("FSD/Content/B.uasset"), b"b").unwrap();
+        fs::remove_file(&pak).unwrap();
+
+        // first sighting of a change only starts the debounce
+        assert_eq!(watcher.poll(), None);
+        let mut changes = watcher.poll().unwrap();
+        changes.sort_by(|a, b| a.spec.url.cmp(&b.spec.url));
+        assert_eq!(
+            changes,
+            [
+                ModChange {
+                    spec: specs[1].clone(),
+                    kind: ModChangeKind::Modified,
+                    files: 2,
+                },
+                ModChange {
+                    spec: specs[0].clone(),
+                    kind: ModChangeKind::Removed,
+                    files: 1,
+                },
+            ]
+        );
+        assert_eq!(watcher.poll(), None);
+
+        fs::write(&pak, b"pak").unwrap();
+        assert_eq!(watcher.poll(), None);
+        assert_eq!(
+            watcher.poll().unwrap(),
+            [ModChange {
+                spec: specs[0].clone(),
+                kind: ModChangeKind::Added,
+                files: 1,
+            }]
+        );
+
+        // mods created after watching started are picked up
+        fs::create_dir_all(later_dir.join("FSD/Content")).unwrap();
+        fs::write(later_dir.join("FSD/Content/C.uasset"), b"c").unwrap();
+        assert_eq!(watcher.poll(), None);
+        assert_eq!(
+            watcher.poll().unwrap(),
+            [ModChange {
+                spec: specs[3].clone(),
+                kind: ModChangeKind::Added,
+                files: 1,
+            }]
+        );
+    }
+}
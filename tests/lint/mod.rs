@@ -1,7 +1,9 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use mint::mod_lints::{LintId, LintReport, SplitAssetPair};
+use mint::conflicts::{Conflict, ConflictReport, ConflictRules};
+use mint::mod_lints::{IntegrationPath, LintId, SplitAssetPair};
 use mint::providers::ModSpecification;
 
 #[test]
@@ -21,15 +23,39 @@ pub fn test_lint_conflicting_files() {
     };
     let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
 
-    let LintReport {
-        conflicting_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::CONFLICTING].into(), mods.into(), None).unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::CONFLICTING].into(), mods.clone().into(), None)
+            .unwrap();
+    let conflicting_mods = report.get::<ConflictReport>(LintId::CONFLICTING);
 
     println!("{:#?}", conflicting_mods);
 
     assert_eq!(
         conflicting_mods.unwrap().get("fsd/content/a.uexp"),
-        Some(&[a_spec, b_spec].into())
+        Some(&Conflict {
+            mods: vec![a_spec.clone(), b_spec.clone()],
+            winner: b_spec,
+        })
+    );
+
+    let rules = ConflictRules {
+        overrides: [("fsd/content/a".to_string(), a_spec.clone())].into(),
+    };
+    let report = mint::mod_lints::run_lints_with_conflict_rules(
+        &[LintId::CONFLICTING].into(),
+        mods.into(),
+        None,
+        rules,
+    )
+    .unwrap();
+    let conflicting_mods = report.get::<ConflictReport>(LintId::CONFLICTING);
+
+    assert_eq!(
+        conflicting_mods
+            .unwrap()
+            .get("fsd/content/a.uexp")
+            .map(|c| &c.winner),
+        Some(&a_spec)
     );
 }
 
@@ -49,9 +75,10 @@ pub fn test_lint_shader() {
     };
     let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
 
-    let LintReport {
-        shader_file_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::SHADER_FILES].into(), mods.into(), None).unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::SHADER_FILES].into(), mods.into(), None).unwrap();
+    let shader_file_mods =
+        report.get::<BTreeMap<ModSpecification, BTreeSet<String>>>(LintId::SHADER_FILES);
 
     println!("{:#?}", shader_file_mods);
 
@@ -77,11 +104,11 @@ pub fn test_lint_asset_registry_bin() {
     };
     let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
 
-    let LintReport {
-        asset_register_bin_mods,
-        ..
-    } = mint::mod_lints::run_lints(&[LintId::ASSET_REGISTRY_BIN].into(), mods.into(), None)
-        .unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::ASSET_REGISTRY_BIN].into(), mods.into(), None)
+            .unwrap();
+    let asset_register_bin_mods =
+        report.get::<BTreeMap<ModSpecification, BTreeSet<String>>>(LintId::ASSET_REGISTRY_BIN);
 
     println!("{:#?}", asset_register_bin_mods);
 
@@ -102,11 +129,11 @@ pub fn test_lint_outdated_pak_version() {
     };
     let mods = [(outdated_spec.clone(), outdated_pak_path)];
 
-    let LintReport {
-        outdated_pak_version_mods,
-        ..
-    } = mint::mod_lints::run_lints(&[LintId::OUTDATED_PAK_VERSION].into(), mods.into(), None)
-        .unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::OUTDATED_PAK_VERSION].into(), mods.into(), None)
+            .unwrap();
+    let outdated_pak_version_mods =
+        report.get::<BTreeMap<ModSpecification, repak::Version>>(LintId::OUTDATED_PAK_VERSION);
 
     println!("{:#?}", outdated_pak_version_mods);
 
@@ -127,9 +154,9 @@ pub fn test_lint_empty_archive() {
     };
     let mods = [(empty_archive_spec.clone(), empty_archive_path)];
 
-    let LintReport {
-        empty_archive_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::EMPTY_ARCHIVE].into(), mods.into(), None).unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::EMPTY_ARCHIVE].into(), mods.into(), None).unwrap();
+    let empty_archive_mods = report.get::<BTreeSet<ModSpecification>>(LintId::EMPTY_ARCHIVE);
 
     println!("{:#?}", empty_archive_mods);
 
@@ -155,15 +182,14 @@ pub fn test_lint_only_non_pak_files() {
         (only_non_pak_spec.clone(), only_non_pak_path),
     ];
 
-    let LintReport {
-        archive_with_only_non_pak_files_mods,
-        ..
-    } = mint::mod_lints::run_lints(
+    let report = mint::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES].into(),
         mods.into(),
         None,
     )
     .unwrap();
+    let archive_with_only_non_pak_files_mods =
+        report.get::<BTreeSet<ModSpecification>>(LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES);
 
     println!("{:#?}", archive_with_only_non_pak_files_mods);
 
@@ -183,15 +209,14 @@ pub fn test_lint_multi_pak_archive() {
     };
     let mods = [(multiple_paks_spec.clone(), multiple_paks_archive_path)];
 
-    let LintReport {
-        archive_with_multiple_paks_mods,
-        ..
-    } = mint::mod_lints::run_lints(
+    let report = mint::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_MULTIPLE_PAKS].into(),
         mods.into(),
         None,
     )
     .unwrap();
+    let archive_with_multiple_paks_mods =
+        report.get::<BTreeSet<ModSpecification>>(LintId::ARCHIVE_WITH_MULTIPLE_PAKS);
 
     println!("{:#?}", archive_with_multiple_paks_mods);
 
@@ -211,10 +236,10 @@ pub fn test_lint_non_asset_files() {
     };
     let mods = [(non_asset_files_spec.clone(), non_asset_files_path)];
 
-    let LintReport {
-        non_asset_file_mods,
-        ..
-    } = mint::mod_lints::run_lints(&[LintId::NON_ASSET_FILES].into(), mods.into(), None).unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::NON_ASSET_FILES].into(), mods.into(), None).unwrap();
+    let non_asset_file_mods =
+        report.get::<BTreeMap<ModSpecification, BTreeSet<String>>>(LintId::NON_ASSET_FILES);
 
     println!("{:#?}", non_asset_file_mods);
 
@@ -235,10 +260,12 @@ pub fn test_lint_split_asset_pairs() {
     };
     let mods = [(split_asset_pairs_spec.clone(), split_asset_pairs_path)];
 
-    let LintReport {
-        split_asset_pairs_mods,
-        ..
-    } = mint::mod_lints::run_lints(&[LintId::SPLIT_ASSET_PAIRS].into(), mods.into(), None).unwrap();
+    let report =
+        mint::mod_lints::run_lints(&[LintId::SPLIT_ASSET_PAIRS].into(), mods.into(), None).unwrap();
+    let split_asset_pairs_mods = report
+        .get::<BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>>>(
+            LintId::SPLIT_ASSET_PAIRS,
+        );
 
     println!("{:#?}", split_asset_pairs_mods);
 
@@ -276,15 +303,14 @@ pub fn test_lint_unmodified_game_assets() {
         unmodified_game_assets_path,
     )];
 
-    let LintReport {
-        unmodified_game_assets_mods,
-        ..
-    } = mint::mod_lints::run_lints(
+    let report = mint::mod_lints::run_lints(
         &[LintId::UNMODIFIED_GAME_ASSETS].into(),
         mods.into(),
         Some(reference_pak_path),
     )
     .unwrap();
+    let unmodified_game_assets_mods =
+        report.get::<BTreeMap<ModSpecification, BTreeSet<String>>>(LintId::UNMODIFIED_GAME_ASSETS);
 
     println!("{:#?}", unmodified_game_assets_mods);
 
@@ -295,3 +321,61 @@ pub fn test_lint_unmodified_game_assets() {
         Some(&["a.uexp".to_string(), "a.uasset".to_string()].into())
     );
 }
+
+#[test]
+pub fn test_lint_integration_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let pak_path = dir.path().join("integration_paths.pak");
+    let mut pak_writer = repak::PakBuilder::new().writer(
+        std::io::BufWriter::new(std::fs::File::create(&pak_path).unwrap()),
+        repak::Version::V11,
+        "../../../".to_string(),
+        None,
+    );
+    for path in [
+        "FSD/Content/Game/BP_GameInstance.uasset",
+        "FSD/Content/Game/BP_GameInstance.uexp",
+        "FSD/Content/_mint/BPL_MINT.uasset",
+        "FSD/Content/_mint/MyMod.uasset",
+        "FSD/Content/Game/BP_GameInstance2.uasset",
+    ] {
+        pak_writer.write_file(path, b"").unwrap();
+    }
+    pak_writer.write_index().unwrap();
+
+    let integration_paths_spec = ModSpecification {
+        url: "integration_paths".to_string(),
+    };
+    let mods = [(integration_paths_spec.clone(), pak_path)];
+
+    let report =
+        mint::mod_lints::run_lints(&[LintId::INTEGRATION_PATHS].into(), mods.into(), None).unwrap();
+    let integration_path_mods = report
+        .get::<BTreeMap<ModSpecification, BTreeMap<String, IntegrationPath>>>(
+            LintId::INTEGRATION_PATHS,
+        );
+
+    println!("{:#?}", integration_path_mods);
+
+    assert_eq!(
+        integration_path_mods.unwrap().get(&integration_paths_spec),
+        Some(
+            &[
+                (
+                    "fsd/content/_mint/bpl_mint.uasset".to_string(),
+                    IntegrationPath::Replaced
+                ),
+                (
+                    "fsd/content/game/bp_gameinstance.uasset".to_string(),
+                    IntegrationPath::Patched
+                ),
+                (
+                    "fsd/content/game/bp_gameinstance.uexp".to_string(),
+                    IntegrationPath::Patched
+                ),
+            ]
+            .into()
+        )
+    );
+    assert_eq!(report.findings().len(), 2);
+}